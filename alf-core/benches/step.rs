@@ -0,0 +1,30 @@
+//! Times `World::step` on growing grids. Compare runs with and without the
+//! `parallel` feature to see how stepping scales across threads:
+//!
+//! ```text
+//! cargo bench -p alf-core
+//! cargo bench -p alf-core --no-default-features
+//! ```
+
+use alf_core::World;
+use std::time::Instant;
+
+const STEPS: u32 = 50;
+
+fn main() {
+    for size in [100, 250, 500, 1000, 2000] {
+        let mut world = World::new(size, size, 0);
+        world.populate(size * size / 20);
+
+        let start = Instant::now();
+        for _ in 0..STEPS {
+            world.step();
+        }
+        let per_step = start.elapsed() / STEPS;
+
+        println!(
+            "{size}x{size} ({} cells): {per_step:?} per step",
+            size * size
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use crate::SpeciesId;
+use serde::{Deserialize, Serialize};
+
+/// How a species' cells have died so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deaths {
+    /// Too few or too many neighbours, by Conway's rules.
+    pub neighbours: u64,
+    /// Killed by enemy cells.
+    pub combat: u64,
+    /// Killed by damage from outside the safe zone or hazards alone.
+    pub environment: u64,
+    /// Reached their species' lifespan.
+    pub old_age: u64,
+}
+
+impl Deaths {
+    pub fn total(&self) -> u64 {
+        self.neighbours + self.combat + self.environment + self.old_age
+    }
+
+    pub(crate) fn record(&mut self, cause: DeathCause) {
+        match cause {
+            DeathCause::Neighbours => self.neighbours += 1,
+            DeathCause::Combat(_) => self.combat += 1,
+            DeathCause::Environment => self.environment += 1,
+            DeathCause::OldAge => self.old_age += 1,
+        }
+    }
+
+    pub(crate) fn add(&mut self, other: &Deaths) {
+        self.neighbours += other.neighbours;
+        self.combat += other.combat;
+        self.environment += other.environment;
+        self.old_age += other.old_age;
+    }
+}
+
+/// Why a cell died in a step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DeathCause {
+    Neighbours,
+    /// Killed by enemies, credited to the species that dealt the most damage.
+    Combat(SpeciesId),
+    Environment,
+    OldAge,
+}
@@ -0,0 +1,46 @@
+use std::fmt;
+
+/// Why saving or loading a world or replay failed.
+#[derive(Debug)]
+pub enum SimIoError {
+    Io(std::io::Error),
+    /// The world or replay could not be serialized.
+    Encode(String),
+    /// The file has the right header but its contents are damaged.
+    Corrupt(String),
+    /// The file isn't a save or replay at all.
+    UnknownFormat,
+    Truncated,
+    /// The file was written by a build with a newer or unknown format.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SimIoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimIoError::Io(err) => write!(f, "{err}"),
+            SimIoError::Encode(err) => write!(f, "cannot encode: {err}"),
+            SimIoError::Corrupt(err) => write!(f, "file is corrupt: {err}"),
+            SimIoError::UnknownFormat => write!(f, "not an artificial life fight file"),
+            SimIoError::Truncated => write!(f, "file is truncated"),
+            SimIoError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimIoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SimIoError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SimIoError {
+    fn from(err: std::io::Error) -> Self {
+        SimIoError::Io(err)
+    }
+}
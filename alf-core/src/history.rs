@@ -0,0 +1,599 @@
+use crate::{Cell, Deaths, SafeZone, SimIoError, Species, Topology, World};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::mem::size_of;
+
+/// How snapshots between keyframes are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryMode {
+    /// Store the cells that changed since the previous snapshot.
+    Delta,
+    /// Store nothing but the tick, and rebuild the snapshot by stepping the
+    /// world forward from the previous one. This relies on stepping being
+    /// deterministic, so any edit made to the world between two snapshots
+    /// must be recorded with `push_keyframe` instead.
+    Resimulate,
+}
+
+/// Bounded buffer of past world snapshots, oldest first.
+///
+/// Only every `keyframe_interval`th snapshot stores a full copy of the world;
+/// the ones in between are stored as described by the `HistoryMode` and are
+/// rebuilt from the nearest keyframe when requested. Once either
+/// the snapshot count or the estimated memory use goes over its cap the
+/// oldest snapshots are evicted; the newest one is always kept.
+#[derive(Serialize, Deserialize)]
+pub struct History {
+    snapshots: VecDeque<Snapshot>,
+    // Full copy of the newest snapshot, which the next delta is taken against
+    latest: Option<World>,
+    since_keyframe: usize,
+    bytes: usize,
+    max_snapshots: usize,
+    max_bytes: usize,
+    keyframe_interval: usize,
+    mode: HistoryMode,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+enum Snapshot {
+    Keyframe(World),
+    Delta(WorldDelta),
+    Replay { tick: u64 },
+}
+
+impl Snapshot {
+    fn tick(&self) -> u64 {
+        match self {
+            Snapshot::Keyframe(world) => world.tick,
+            Snapshot::Delta(delta) => delta.tick,
+            Snapshot::Replay { tick } => *tick,
+        }
+    }
+
+    fn bytes(&self) -> usize {
+        match self {
+            Snapshot::Keyframe(world) => world.estimated_bytes(),
+            Snapshot::Delta(delta) => delta.estimated_bytes(),
+            Snapshot::Replay { .. } => size_of::<Self>(),
+        }
+    }
+
+    /// Turns the world at the snapshot before this one into this snapshot.
+    fn apply(&self, world: &mut World) {
+        match self {
+            Snapshot::Keyframe(keyframe) => *world = keyframe.clone(),
+            Snapshot::Delta(delta) => delta.apply(world),
+            Snapshot::Replay { tick } => {
+                while world.tick < *tick {
+                    world.step();
+                }
+            }
+        }
+    }
+}
+
+/// Changes that turn one snapshot into the next.
+#[derive(Clone, Serialize, Deserialize)]
+struct WorldDelta {
+    tick: u64,
+    rng: ChaCha8Rng,
+    species: Option<Vec<Species>>,
+    safe_zone: Option<SafeZone>,
+    kills: Vec<u64>,
+    deaths: Vec<Deaths>,
+    topology: Topology,
+    walls: Option<Vec<bool>>,
+    hazards: Option<Vec<u8>>,
+    // Cells that differ from the current generation of the snapshot before
+    current: Vec<(u32, Option<Cell>)>,
+    // Cells of the previous generation that differ from the current one,
+    // which is a single tick's worth of changes
+    previous: Vec<(u32, Option<Cell>)>,
+}
+
+impl WorldDelta {
+    fn new(base: &World, world: &World) -> Self {
+        debug_assert_eq!(base.current.len(), world.current.len());
+
+        Self {
+            tick: world.tick,
+            rng: world.rng.clone(),
+            species: (base.species != world.species).then(|| world.species.clone()),
+            safe_zone: world.safe_zone,
+            kills: world.kills.clone(),
+            deaths: world.deaths.clone(),
+            topology: world.topology,
+            walls: (base.walls != world.walls).then(|| world.walls.clone()),
+            hazards: (base.hazards != world.hazards).then(|| world.hazards.clone()),
+            current: changed_cells(&base.current, &world.current),
+            previous: changed_cells(&world.current, &world.previous),
+        }
+    }
+
+    fn apply(&self, world: &mut World) {
+        world.tick = self.tick;
+        world.rng = self.rng.clone();
+        if let Some(species) = &self.species {
+            world.species = species.clone();
+        }
+        world.safe_zone = self.safe_zone;
+        world.kills.clone_from(&self.kills);
+        world.deaths.clone_from(&self.deaths);
+        world.topology = self.topology;
+        if let Some(walls) = &self.walls {
+            world.walls.clone_from(walls);
+        }
+        if let Some(hazards) = &self.hazards {
+            world.hazards.clone_from(hazards);
+        }
+
+        for (idx, cell) in &self.current {
+            world.current[*idx as usize] = *cell;
+        }
+
+        world.previous.copy_from_slice(&world.current);
+        for (idx, cell) in &self.previous {
+            world.previous[*idx as usize] = *cell;
+        }
+    }
+
+    fn estimated_bytes(&self) -> usize {
+        let species = self.species.as_ref().map_or(0, |species| {
+            species.capacity() * size_of::<Species>()
+                + species
+                    .iter()
+                    .map(|species| species.name.capacity())
+                    .sum::<usize>()
+        });
+        let cells =
+            (self.current.capacity() + self.previous.capacity()) * size_of::<(u32, Option<Cell>)>();
+
+        let walls = self.walls.as_ref().map_or(0, Vec::capacity)
+            + self.hazards.as_ref().map_or(0, Vec::capacity);
+
+        size_of::<Self>()
+            + species
+            + cells
+            + walls
+            + self.kills.capacity() * size_of::<u64>()
+            + self.deaths.capacity() * size_of::<Deaths>()
+    }
+}
+
+fn changed_cells(from: &[Option<Cell>], to: &[Option<Cell>]) -> Vec<(u32, Option<Cell>)> {
+    from.iter()
+        .zip(to)
+        .enumerate()
+        .filter(|(_, (from, to))| from != to)
+        .map(|(idx, (_, to))| (idx as u32, *to))
+        .collect()
+}
+
+impl History {
+    pub fn new(max_snapshots: usize, max_bytes: usize, keyframe_interval: usize) -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            latest: None,
+            since_keyframe: 0,
+            bytes: 0,
+            max_snapshots: max_snapshots.max(1),
+            max_bytes,
+            keyframe_interval: keyframe_interval.max(1),
+            mode: HistoryMode::Delta,
+        }
+    }
+
+    /// An empty history with the same caps and mode as this one.
+    pub fn empty_like(&self) -> Self {
+        Self::new(self.max_snapshots, self.max_bytes, self.keyframe_interval).with_mode(self.mode)
+    }
+
+    pub fn with_mode(mut self, mode: HistoryMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn mode(&self) -> HistoryMode {
+        self.mode
+    }
+
+    /// Changes how future snapshots are stored; existing ones are kept as is.
+    pub fn set_mode(&mut self, mode: HistoryMode) {
+        self.mode = mode;
+    }
+
+    pub fn push(&mut self, world: &World) {
+        let snapshot = match &self.latest {
+            Some(latest) if self.since_keyframe + 1 < self.keyframe_interval => {
+                self.since_keyframe += 1;
+                match self.mode {
+                    HistoryMode::Delta => Snapshot::Delta(WorldDelta::new(latest, world)),
+                    HistoryMode::Resimulate if latest.tick < world.tick => {
+                        Snapshot::Replay { tick: world.tick }
+                    }
+                    HistoryMode::Resimulate => {
+                        self.since_keyframe = 0;
+                        Snapshot::Keyframe(world.clone())
+                    }
+                }
+            }
+            _ => {
+                self.since_keyframe = 0;
+                Snapshot::Keyframe(world.clone())
+            }
+        };
+
+        self.push_snapshot(snapshot, world);
+    }
+
+    /// Stores `world` in full, for states that can't be reached by stepping
+    /// the previous snapshot, such as after an edit.
+    pub fn push_keyframe(&mut self, world: &World) {
+        self.since_keyframe = 0;
+        self.push_snapshot(Snapshot::Keyframe(world.clone()), world);
+    }
+
+    fn push_snapshot(&mut self, snapshot: Snapshot, world: &World) {
+        self.bytes += snapshot.bytes();
+        self.snapshots.push_back(snapshot);
+        self.latest = Some(world.clone());
+
+        while self.snapshots.len() > 1
+            && (self.snapshots.len() > self.max_snapshots || self.bytes > self.max_bytes)
+        {
+            self.pop_front();
+        }
+    }
+
+    /// Rebuilds the snapshot at `index` from its keyframe.
+    pub fn get(&self, index: usize) -> Option<World> {
+        if index >= self.snapshots.len() {
+            return None;
+        }
+
+        let keyframe = (0..=index)
+            .rev()
+            .find(|i| matches!(self.snapshots[*i], Snapshot::Keyframe(_)))?;
+
+        let mut world = match &self.snapshots[keyframe] {
+            Snapshot::Keyframe(world) => world.clone(),
+            _ => unreachable!(),
+        };
+
+        for snapshot in self.snapshots.range(keyframe + 1..=index) {
+            snapshot.apply(&mut world);
+        }
+
+        Some(world)
+    }
+
+    /// Index of the newest snapshot taken at or before `tick`.
+    pub fn index_at_or_before(&self, tick: u64) -> Option<usize> {
+        let after = self
+            .snapshots
+            .partition_point(|snapshot| snapshot.tick() <= tick);
+        after.checked_sub(1)
+    }
+
+    /// Rebuilds the world as it was at exactly `tick` by stepping forward
+    /// from the newest snapshot before it.
+    pub fn world_at_tick(&self, tick: u64) -> Option<World> {
+        let mut world = self.get(self.index_at_or_before(tick)?)?;
+        while world.tick < tick {
+            world.step();
+        }
+
+        Some(world)
+    }
+
+    /// A copy of the snapshots needed to rebuild every tick from `from` to
+    /// `to`, or `None` if `from` is older than the oldest snapshot.
+    pub fn slice(&self, from: u64, to: u64) -> Option<History> {
+        let first = self.index_at_or_before(from)?;
+        let keyframe = (0..=first)
+            .rev()
+            .find(|i| matches!(self.snapshots[*i], Snapshot::Keyframe(_)))?;
+        let last = self.index_at_or_before(to)?.max(first);
+
+        let mut slice = self.empty_like();
+        slice.snapshots = self.snapshots.range(keyframe..=last).cloned().collect();
+        slice.bytes = slice.snapshots.iter().map(Snapshot::bytes).sum();
+        slice.latest = slice.get(slice.snapshots.len() - 1);
+        slice.since_keyframe = slice
+            .snapshots
+            .iter()
+            .rev()
+            .take_while(|snapshot| !matches!(snapshot, Snapshot::Keyframe(_)))
+            .count();
+        Some(slice)
+    }
+
+    /// Tick of the oldest snapshot still held.
+    pub fn first_tick(&self) -> Option<u64> {
+        self.snapshots.front().map(Snapshot::tick)
+    }
+
+    pub fn latest(&self) -> Option<&World> {
+        self.latest.as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Estimated memory held by the stored snapshots.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    pub fn max_snapshots(&self) -> usize {
+        self.max_snapshots
+    }
+
+    /// Drops every snapshot after `index`, e.g. when the simulation resumes
+    /// from a rewound point and the old future no longer applies.
+    pub fn truncate(&mut self, index: usize) {
+        if index + 1 >= self.snapshots.len() {
+            return;
+        }
+
+        while self.snapshots.len() > index + 1 {
+            if let Some(snapshot) = self.snapshots.pop_back() {
+                self.bytes -= snapshot.bytes();
+            }
+        }
+
+        self.latest = self.get(index);
+        self.since_keyframe = self
+            .snapshots
+            .iter()
+            .rev()
+            .take_while(|snapshot| !matches!(snapshot, Snapshot::Keyframe(_)))
+            .count();
+    }
+
+    /// Evicts the oldest snapshot unless it's the only one.
+    pub fn evict_oldest(&mut self) {
+        if self.snapshots.len() > 1 {
+            self.pop_front();
+        }
+    }
+
+    /// Checks that a history read from a file is consistent, so rebuilding
+    /// its snapshots can't panic.
+    pub fn validate(&self) -> Result<(), SimIoError> {
+        let corrupt = |message: &str| Err(SimIoError::Corrupt(message.to_string()));
+
+        let Some(latest) = &self.latest else {
+            return corrupt("a branch has no snapshots");
+        };
+        latest.validate()?;
+        if !matches!(self.snapshots.front(), Some(Snapshot::Keyframe(_))) {
+            return corrupt("a branch doesn't start with a full snapshot");
+        }
+
+        let area = latest.current.len();
+        for snapshot in &self.snapshots {
+            match snapshot {
+                Snapshot::Keyframe(world) => {
+                    world.validate()?;
+                    if world.current.len() != area {
+                        return corrupt("a branch changes the world's size");
+                    }
+                }
+                Snapshot::Delta(delta) => {
+                    // Species can be edited but never added or removed
+                    let species_count = latest.species.len();
+                    let misfit =
+                        |len: Option<usize>| len.is_some_and(|len| len != 0 && len != area);
+                    let mut cells = delta.current.iter().chain(&delta.previous);
+                    let outside = cells.any(|(idx, cell)| {
+                        *idx as usize >= area
+                            || cell.is_some_and(|cell| cell.species as usize >= species_count)
+                    });
+                    if outside
+                        || misfit(delta.walls.as_ref().map(Vec::len))
+                        || misfit(delta.hazards.as_ref().map(Vec::len))
+                    {
+                        return corrupt("a snapshot doesn't fit the world");
+                    }
+
+                    if let Some(species) = &delta.species {
+                        if species.len() != species_count {
+                            return corrupt("a snapshot adds or removes species");
+                        }
+                        let mut prey = species.iter().flat_map(|s| s.prey.iter().flatten());
+                        if prey.any(|id| *id as usize >= species_count) {
+                            return corrupt("a species preys on a species that doesn't exist");
+                        }
+                    }
+                    if delta.kills.len() > species_count || delta.deaths.len() > species_count {
+                        return corrupt("a snapshot has more kill or death counts than species");
+                    }
+                }
+                Snapshot::Replay { .. } => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.latest = None;
+        self.since_keyframe = 0;
+        self.bytes = 0;
+    }
+
+    // The front snapshot is always a keyframe; when it goes, the snapshot
+    // after it is rebuilt from it to become the new keyframe
+    fn pop_front(&mut self) {
+        let Some(Snapshot::Keyframe(mut world)) = self.snapshots.pop_front() else {
+            return;
+        };
+        self.bytes -= world.estimated_bytes();
+
+        if let Some(next) = self.snapshots.front() {
+            if matches!(next, Snapshot::Keyframe(_)) {
+                return;
+            }
+
+            next.apply(&mut world);
+
+            let keyframe = Snapshot::Keyframe(world);
+            self.bytes -= next.bytes();
+            self.bytes += keyframe.bytes();
+            self.snapshots[0] = keyframe;
+            self.since_keyframe = self.since_keyframe.min(self.snapshots.len() - 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same(a: &World, b: &World) {
+        assert_eq!(
+            bincode::serialize(a).unwrap(),
+            bincode::serialize(b).unwrap()
+        );
+    }
+
+    // Steps a world 60 ticks, snapshotting every other tick and editing it
+    // once along the way, and checks every snapshot rebuilds exactly
+    fn check_reconstruction(mode: HistoryMode, max_snapshots: usize) {
+        let mut history = History::new(max_snapshots, usize::MAX, 4).with_mode(mode);
+        let mut world = World::new(30, 20, 11);
+        world.populate(40);
+
+        let mut recorded = vec![world.clone()];
+        history.push(&world);
+        for tick in 1..=60 {
+            world.step();
+            if tick == 25 {
+                world.spawn(3, 3, 1);
+                world.set_wall(10, 10, true);
+                history.push_keyframe(&world);
+                recorded.push(world.clone());
+            } else if tick % 2 == 0 {
+                history.push(&world);
+                recorded.push(world.clone());
+            }
+        }
+
+        let kept = &recorded[recorded.len() - history.len()..];
+        for (index, expected) in kept.iter().enumerate() {
+            assert_same(&history.get(index).unwrap(), expected);
+        }
+        assert_same(history.latest().unwrap(), &world);
+
+        let first = history.first_tick().unwrap();
+        assert_eq!(first, kept[0].tick());
+        let mut live = kept[0].clone();
+        while live.tick() < 24 {
+            live.step();
+        }
+        assert_same(&history.world_at_tick(live.tick()).unwrap(), &live);
+    }
+
+    #[test]
+    fn delta_snapshots_rebuild_the_live_world() {
+        check_reconstruction(HistoryMode::Delta, 1000);
+    }
+
+    #[test]
+    fn resimulated_snapshots_rebuild_the_live_world() {
+        check_reconstruction(HistoryMode::Resimulate, 1000);
+    }
+
+    #[test]
+    fn evicted_keyframes_are_rebuilt() {
+        check_reconstruction(HistoryMode::Delta, 7);
+        check_reconstruction(HistoryMode::Resimulate, 7);
+    }
+
+    #[test]
+    fn malformed_deltas_are_rejected() {
+        let history = || {
+            let mut history = History::new(100, usize::MAX, 4);
+            let mut world = World::new(10, 10, 1);
+            world.populate(10);
+            for _ in 0..3 {
+                history.push(&world);
+                world.step();
+            }
+            history
+        };
+        history().validate().unwrap();
+
+        let corruptions: [fn(&mut WorldDelta); 4] = [
+            |delta| delta.species = Some(vec![Species::new("Lone", [0; 4])]),
+            |delta| {
+                let mut species = Species::defaults();
+                species[0].prey = Some(vec![7]);
+                delta.species = Some(species);
+            },
+            |delta| delta.kills = vec![0; 3],
+            |delta| delta.deaths = vec![Deaths::default(); 3],
+        ];
+        for corrupt in corruptions {
+            let mut history = history();
+            let Some(Snapshot::Delta(delta)) = history.snapshots.get_mut(1) else {
+                panic!("the second snapshot should be a delta");
+            };
+            corrupt(delta);
+            assert!(matches!(history.validate(), Err(SimIoError::Corrupt(_))));
+        }
+    }
+
+    #[test]
+    fn slices_rebuild_their_ticks() {
+        let mut history = History::new(100, usize::MAX, 4).with_mode(HistoryMode::Resimulate);
+        let mut world = World::new(10, 10, 1);
+        world.populate(10);
+        let mut recorded = vec![];
+        for tick in 0..20 {
+            if tick == 9 {
+                world.spawn(5, 5, 0);
+                history.push_keyframe(&world);
+            } else {
+                history.push(&world);
+            }
+            recorded.push(world.clone());
+            world.step();
+        }
+
+        assert!(history.slice(25, 30).is_some());
+        let slice = history.slice(6, 12).unwrap();
+        assert!(slice.len() < history.len());
+        for expected in &recorded[6..=12] {
+            assert_same(&slice.world_at_tick(expected.tick()).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn truncate_drops_the_future() {
+        let mut history = History::new(100, usize::MAX, 4);
+        let mut world = World::new(10, 10, 1);
+        world.populate(10);
+        for _ in 0..10 {
+            history.push(&world);
+            world.step();
+        }
+
+        history.truncate(5);
+        assert_eq!(history.len(), 6);
+        assert_same(history.latest().unwrap(), &history.get(5).unwrap());
+    }
+}
@@ -0,0 +1,763 @@
+//! Simulation core for artificial life fight, free of any windowing or
+//! rendering dependency so it can be driven headlessly.
+
+mod deaths;
+mod error;
+mod hash;
+mod history;
+mod migrations;
+mod obstacle;
+mod replay;
+mod rules;
+mod save;
+mod species;
+mod timeline;
+mod topology;
+mod zone;
+
+pub use deaths::Deaths;
+pub use error::SimIoError;
+pub use history::{History, HistoryMode};
+pub use migrations::SAVE_VERSION;
+pub use obstacle::Obstacle;
+pub use replay::{Edit, Replay};
+pub use rules::{MatchEnd, MatchRules};
+pub use save::{load_world, save_world, ResumeCheck, SaveFile, SaveFormat, RESUME_TICKS};
+pub use species::{Species, SpeciesId};
+pub use timeline::{Branch, Timeline};
+pub use topology::Topology;
+pub use zone::SafeZone;
+
+use deaths::DeathCause;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A live cell. Dead cells are stored as `None`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cell {
+    pub species: SpeciesId,
+    pub health: u8,
+    /// Tick the cell was born on, truncated to 32 bits. Storing the birth
+    /// rather than the age keeps cells unchanged while they merely survive.
+    #[serde(default)]
+    pub born: u32,
+}
+
+impl Cell {
+    /// Ticks the cell has lived by `tick`.
+    pub fn age(&self, tick: u64) -> u32 {
+        (tick as u32).wrapping_sub(self.born)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct World {
+    width: usize,
+    height: usize,
+    tick: u64,
+    species: Vec<Species>,
+    current: Vec<Option<Cell>>,
+    previous: Vec<Option<Cell>>,
+    seed: u64,
+    rng: ChaCha8Rng,
+    #[serde(default)]
+    safe_zone: Option<SafeZone>,
+    // Enemy cells killed by each species so far
+    #[serde(default)]
+    kills: Vec<u64>,
+    #[serde(default)]
+    topology: Topology,
+    // Cells life can't exist in, indexed like `current`. Empty until the
+    // first wall goes up
+    #[serde(default)]
+    walls: Vec<bool>,
+    // Damage each cell takes per tick from hazards, indexed like `current`.
+    // Empty until the first hazard is placed
+    #[serde(default)]
+    hazards: Vec<u8>,
+    // How each species' cells have died so far
+    #[serde(default)]
+    deaths: Vec<Deaths>,
+}
+
+impl World {
+    /// Creates an empty world with the default species. Every random
+    /// decision the world makes is drawn from an RNG seeded with `seed`, so
+    /// equal seeds give equal runs.
+    pub fn new(width: usize, height: usize, seed: u64) -> Self {
+        Self::with_species(width, height, seed, Species::defaults())
+    }
+
+    pub fn with_species(width: usize, height: usize, seed: u64, species: Vec<Species>) -> Self {
+        assert!(
+            !species.is_empty() && species.len() <= SpeciesId::MAX as usize + 1,
+            "a world needs between 1 and {} species",
+            SpeciesId::MAX as usize + 1
+        );
+
+        let current = vec![None; width * height];
+        let previous = current.clone();
+        let species_count = species.len();
+
+        Self {
+            width,
+            height,
+            tick: 0,
+            species,
+            current,
+            previous,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            safe_zone: None,
+            kills: vec![0; species_count],
+            topology: Topology::default(),
+            walls: vec![],
+            hazards: vec![],
+            deaths: vec![Deaths::default(); species_count],
+        }
+    }
+
+    /// Checks that a world read from a file is consistent, so stepping or
+    /// drawing it can't panic.
+    pub fn validate(&self) -> Result<(), SimIoError> {
+        let corrupt = |message: &str| Err(SimIoError::Corrupt(message.to_string()));
+
+        if self.width == 0 || self.height == 0 {
+            return corrupt("the world has no cells");
+        }
+
+        let Some(area) = self.width.checked_mul(self.height) else {
+            return corrupt("the world is too large");
+        };
+        if self.current.len() != area || self.previous.len() != area {
+            return corrupt("the cell count doesn't match the world's size");
+        }
+        if !self.walls.is_empty() && self.walls.len() != area {
+            return corrupt("the wall count doesn't match the world's size");
+        }
+        if !self.hazards.is_empty() && self.hazards.len() != area {
+            return corrupt("the hazard count doesn't match the world's size");
+        }
+
+        let species_count = self.species.len();
+        if species_count == 0 || species_count > SpeciesId::MAX as usize + 1 {
+            return corrupt("the world has no species or too many");
+        }
+        let unknown = |id: SpeciesId| id as usize >= species_count;
+        let mut cells = self.current.iter().chain(&self.previous).flatten();
+        if cells.any(|cell| unknown(cell.species)) {
+            return corrupt("a cell belongs to a species that doesn't exist");
+        }
+        let mut prey = self.species.iter().flat_map(|s| s.prey.iter().flatten());
+        if prey.any(|id| unknown(*id)) {
+            return corrupt("a species preys on a species that doesn't exist");
+        }
+        if self.kills.len() > species_count || self.deaths.len() > species_count {
+            return corrupt("there are more kill or death counts than species");
+        }
+
+        Ok(())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of generations stepped since the world was created.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn species(&self) -> &[Species] {
+        &self.species
+    }
+
+    /// Hash of the world's whole state, equal for equal worlds on any
+    /// platform.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = hash::Fnv::new();
+        bincode::serialize_into(&mut hasher, self).expect("worlds always encode");
+        hasher.finish()
+    }
+
+    /// Hashes of the states the world goes through over the next `ticks`
+    /// ticks, stepped on a copy so the world itself is left alone.
+    pub fn future_hashes(&self, ticks: usize) -> Vec<u64> {
+        let mut world = self.clone();
+        (0..ticks)
+            .map(|_| {
+                world.step();
+                world.state_hash()
+            })
+            .collect()
+    }
+
+    /// Makes `attacker` attack `victim` or leave it alone. A species that
+    /// attacked every other one keeps attacking the rest.
+    pub fn set_prey(&mut self, attacker: SpeciesId, victim: SpeciesId, preys: bool) {
+        let species_count = self.species.len() as SpeciesId;
+        let species = &mut self.species[attacker as usize];
+        let prey = species
+            .prey
+            .get_or_insert_with(|| (0..species_count).filter(|id| *id != attacker).collect());
+
+        prey.retain(|id| *id != victim);
+        if preys {
+            prey.push(victim);
+            prey.sort_unstable();
+        }
+    }
+
+    /// Number of enemy cells `species` has killed so far. A kill goes to
+    /// the species that dealt the dying cell the most damage.
+    pub fn kills(&self, species: SpeciesId) -> u64 {
+        self.kills.get(species as usize).copied().unwrap_or(0)
+    }
+
+    /// How cells of `species` have died so far.
+    pub fn deaths(&self, species: SpeciesId) -> Deaths {
+        self.deaths
+            .get(species as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn safe_zone(&self) -> Option<&SafeZone> {
+        self.safe_zone.as_ref()
+    }
+
+    /// Turns battle royale mode on or off. The zone's schedule counts from
+    /// tick 0, so it can be set on a running world.
+    pub fn set_safe_zone(&mut self, safe_zone: Option<SafeZone>) {
+        self.safe_zone = safe_zone;
+    }
+
+    /// Radius of the safe zone in cells at the current tick.
+    pub fn safe_zone_radius(&self) -> Option<f32> {
+        self.safe_zone
+            .map(|zone| zone.radius(self.tick, self.width, self.height))
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> Option<Cell> {
+        self.index(x as _, y as _).and_then(|idx| self.current[idx])
+    }
+
+    /// The cell as it was in the generation before the current one.
+    pub fn previous_cell(&self, x: usize, y: usize) -> Option<Cell> {
+        self.index(x as _, y as _)
+            .and_then(|idx| self.previous[idx])
+    }
+
+    pub fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y).is_some()
+    }
+
+    /// Whether the cell was alive in the generation before the current one.
+    pub fn was_alive(&self, x: usize, y: usize) -> bool {
+        self.previous_cell(x, y).is_some()
+    }
+
+    /// Rough number of bytes the world occupies, heap allocations included.
+    pub fn estimated_bytes(&self) -> usize {
+        let cells = (self.current.capacity() + self.previous.capacity())
+            * std::mem::size_of::<Option<Cell>>();
+        let species = self.species.capacity() * std::mem::size_of::<Species>()
+            + self
+                .species
+                .iter()
+                .map(|species| species.name.capacity())
+                .sum::<usize>();
+
+        std::mem::size_of::<Self>()
+            + cells
+            + species
+            + self.walls.capacity()
+            + self.hazards.capacity()
+    }
+
+    /// Brings the cell to life as a member of `species` at full health,
+    /// unless it's a wall.
+    pub fn spawn(&mut self, x: isize, y: isize, species: SpeciesId) {
+        let cell = self.new_cell(species);
+        if let Some(idx) = self.index(x, y) {
+            if !self.walls.get(idx).copied().unwrap_or(false) {
+                self.current[idx] = Some(cell);
+            }
+        }
+    }
+
+    pub fn kill(&mut self, x: isize, y: isize) {
+        if let Some(idx) = self.index(x, y) {
+            self.current[idx] = None;
+        }
+    }
+
+    pub fn is_wall(&self, x: usize, y: usize) -> bool {
+        self.index(x as _, y as _)
+            .and_then(|idx| self.walls.get(idx).copied())
+            .unwrap_or(false)
+    }
+
+    /// Puts up or tears down a wall. Walls never hold a live cell, so
+    /// nothing is born in them and cells can't reach across them.
+    pub fn set_wall(&mut self, x: isize, y: isize, wall: bool) {
+        let Some(idx) = self.index(x, y) else {
+            return;
+        };
+
+        if self.walls.is_empty() {
+            if !wall {
+                return;
+            }
+            self.walls = vec![false; self.width * self.height];
+        }
+
+        self.walls[idx] = wall;
+        if wall {
+            self.current[idx] = None;
+        }
+    }
+
+    /// Damage the cell takes every tick from hazards.
+    pub fn hazard(&self, x: usize, y: usize) -> u8 {
+        self.index(x as _, y as _)
+            .and_then(|idx| self.hazards.get(idx).copied())
+            .unwrap_or(0)
+    }
+
+    /// Makes the cell a hazard dealing `damage` every tick to whatever lives
+    /// there, or clears it with a damage of 0. Cells in a hazard can't heal.
+    pub fn set_hazard(&mut self, x: isize, y: isize, damage: u8) {
+        let Some(idx) = self.index(x, y) else {
+            return;
+        };
+
+        if self.hazards.is_empty() {
+            if damage == 0 {
+                return;
+            }
+            self.hazards = vec![0; self.width * self.height];
+        }
+
+        self.hazards[idx] = damage;
+    }
+
+    /// Walls off every cell `obstacle` covers. Obstacles are stored as the
+    /// walls they put up, so they're saved with the world.
+    pub fn place_obstacle(&mut self, obstacle: &Obstacle) {
+        for (x, y) in obstacle.cells() {
+            self.set_wall(x, y, true);
+        }
+    }
+
+    /// Walls off everything outside the rectangle at `x`, `y` of the given
+    /// size, leaving an arena the fight is confined to.
+    pub fn enclose(&mut self, x: usize, y: usize, width: usize, height: usize) {
+        for wall_y in 0..self.height {
+            for wall_x in 0..self.width {
+                let inside = (x..x + width).contains(&wall_x) && (y..y + height).contains(&wall_y);
+                if !inside {
+                    self.set_wall(wall_x as _, wall_y as _, true);
+                }
+            }
+        }
+    }
+
+    /// Brings to life `clusters` randomly placed 3x3 rings of cells. The
+    /// world is split into one vertical band per species and each cluster
+    /// belongs to the species whose band it lands in, so teams start apart.
+    pub fn populate(&mut self, clusters: usize) {
+        for _ in 0..clusters {
+            let x = self.rng.gen_range(0..self.width);
+            let y = self.rng.gen_range(0..self.height);
+            let species = (x * self.species.len() / self.width) as SpeciesId;
+
+            let neighbors = get_neighbors(x as _, y as _);
+            neighbors.iter().for_each(|(x, y)| {
+                self.spawn(*x, *y, species);
+            });
+        }
+    }
+
+    pub fn population(&self) -> usize {
+        self.current.iter().flatten().count()
+    }
+
+    /// Number of live cells per species, indexed by `SpeciesId`.
+    pub fn populations(&self) -> Vec<usize> {
+        let mut populations = vec![0; self.species.len()];
+        self.current
+            .iter()
+            .flatten()
+            .for_each(|cell| populations[cell.species as usize] += 1);
+        populations
+    }
+
+    /// The only species with live cells left, once every other one has been
+    /// wiped out. Worlds with a single species never have a winner.
+    pub fn winner(&self) -> Option<SpeciesId> {
+        if self.species.len() < 2 {
+            return None;
+        }
+
+        let mut alive = self
+            .populations()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, population)| *population > 0);
+
+        match (alive.next(), alive.next()) {
+            (Some((id, _)), None) => Some(id as SpeciesId),
+            _ => None,
+        }
+    }
+
+    /// Advances the world by one generation.
+    ///
+    /// Cells are born and die by Conway's rules, and a newborn joins the
+    /// species most of its three parents belong to. A surviving cell also
+    /// takes the attack of every neighbour of a species that preys on it and
+    /// dies once its health runs out; with no such enemies around it heals
+    /// back to full. Outside the safe zone, if there is one, and in hazards cells take
+    /// their damage too. Cells of species with a lifespan die of old age
+    /// once they reach it.
+    pub fn step(&mut self) {
+        let zone = self.safe_zone.map(|zone| {
+            let radius = zone.radius(self.tick, self.width, self.height);
+            (radius, zone.damage)
+        });
+
+        std::mem::swap(&mut self.current, &mut self.previous);
+
+        let generation = Generation {
+            width: self.width,
+            height: self.height,
+            tick: self.tick,
+            species: &self.species,
+            cells: &self.previous,
+            topology: self.topology,
+            walls: &self.walls,
+            hazards: &self.hazards,
+            zone,
+        };
+
+        // Each row only reads the previous generation, so rows can be
+        // written independently and in any order. Each returns the deaths
+        // in it
+        let species_count = self.species.len();
+        let update_row = |(y, row): (usize, &mut [Option<Cell>])| {
+            let mut tally = Tally::new(species_count);
+            for (x, cell) in row.iter_mut().enumerate() {
+                let death;
+                (*cell, death) = generation.next_cell(x, y);
+                if let Some((victim, cause)) = death {
+                    tally.record(victim, cause);
+                }
+            }
+            tally
+        };
+
+        #[cfg(feature = "parallel")]
+        let tally = self
+            .current
+            .par_chunks_mut(self.width)
+            .enumerate()
+            .map(update_row)
+            .reduce(|| Tally::new(species_count), Tally::add);
+
+        #[cfg(not(feature = "parallel"))]
+        let tally = self
+            .current
+            .chunks_mut(self.width)
+            .enumerate()
+            .map(update_row)
+            .fold(Tally::new(species_count), Tally::add);
+
+        // Worlds saved before kills and deaths were counted start from none
+        let mut total = Tally {
+            kills: std::mem::take(&mut self.kills),
+            deaths: std::mem::take(&mut self.deaths),
+        };
+        total.kills.resize(species_count, 0);
+        total.deaths.resize(species_count, Deaths::default());
+        total = total.add(tally);
+        (self.kills, self.deaths) = (total.kills, total.deaths);
+
+        self.tick += 1;
+    }
+
+    fn new_cell(&self, species: SpeciesId) -> Cell {
+        new_cell(&self.species, species, self.tick)
+    }
+
+    fn index(&self, x: isize, y: isize) -> Option<usize> {
+        self.topology.index(self.width, self.height, x, y)
+    }
+}
+
+/// Kills and deaths counted while stepping part of the world.
+struct Tally {
+    kills: Vec<u64>,
+    deaths: Vec<Deaths>,
+}
+
+impl Tally {
+    fn new(species_count: usize) -> Self {
+        Self {
+            kills: vec![0; species_count],
+            deaths: vec![Deaths::default(); species_count],
+        }
+    }
+
+    fn record(&mut self, victim: SpeciesId, cause: DeathCause) {
+        self.deaths[victim as usize].record(cause);
+        if let DeathCause::Combat(killer) = cause {
+            self.kills[killer as usize] += 1;
+        }
+    }
+
+    fn add(mut self, other: Tally) -> Self {
+        self.kills
+            .iter_mut()
+            .zip(other.kills)
+            .for_each(|(total, kills)| *total += kills);
+        self.deaths
+            .iter_mut()
+            .zip(&other.deaths)
+            .for_each(|(total, deaths)| total.add(deaths));
+        self
+    }
+}
+
+/// Read-only view of the previous generation used to compute the next one.
+struct Generation<'a> {
+    width: usize,
+    height: usize,
+    tick: u64,
+    species: &'a [Species],
+    cells: &'a [Option<Cell>],
+    topology: Topology,
+    walls: &'a [bool],
+    hazards: &'a [u8],
+    // Radius of the safe zone and the damage it deals outside
+    zone: Option<(f32, u8)>,
+}
+
+impl Generation<'_> {
+    /// The cell's next state, plus the species and cause of death if it
+    /// died.
+    fn next_cell(&self, x: usize, y: usize) -> (Option<Cell>, Option<(SpeciesId, DeathCause)>) {
+        if self.walls.get(y * self.width + x) == Some(&true) {
+            return (None, None);
+        }
+
+        let neighbors = get_neighbors(x as _, y as _).map(|(x, y)| {
+            match self.topology.index(self.width, self.height, x, y) {
+                Some(idx) => self.cells[idx],
+                _ => None,
+            }
+        });
+        let count = neighbors.iter().flatten().count();
+
+        match self.cells[y * self.width + x] {
+            Some(cell) => {
+                if count != 2 && count != 3 {
+                    return (None, Some((cell.species, DeathCause::Neighbours)));
+                }
+
+                let species = &self.species[cell.species as usize];
+                if species.is_too_old(cell.age(self.tick + 1)) {
+                    return (None, Some((cell.species, DeathCause::OldAge)));
+                }
+
+                let damage = neighbors
+                    .iter()
+                    .flatten()
+                    .filter(|neighbor| self.preys_on(neighbor.species, cell.species))
+                    .map(|neighbor| self.attack(neighbor))
+                    .fold(self.environment_damage(x, y), u8::saturating_add);
+
+                if damage == 0 {
+                    let health = species.health;
+                    return (Some(Cell { health, ..cell }), None);
+                }
+
+                match cell.health.saturating_sub(damage) {
+                    0 => {
+                        let cause = match self.killer(&neighbors, cell.species) {
+                            Some(killer) => DeathCause::Combat(killer),
+                            None => DeathCause::Environment,
+                        };
+                        (None, Some((cell.species, cause)))
+                    }
+                    health => (Some(Cell { health, ..cell }), None),
+                }
+            }
+            None if count == 3 => {
+                let mut parents = [0; 3];
+                neighbors
+                    .iter()
+                    .flatten()
+                    .zip(parents.iter_mut())
+                    .for_each(|(cell, parent)| *parent = cell.species);
+                let species = self.majority_species(&parents, x, y);
+                (Some(new_cell(self.species, species, self.tick + 1)), None)
+            }
+            None => (None, None),
+        }
+    }
+
+    // The enemy species that dealt the most damage, the lowest id on ties.
+    // Nobody gets the kill when the environment alone did it
+    fn killer(&self, neighbors: &[Option<Cell>], victim: SpeciesId) -> Option<SpeciesId> {
+        let mut damage = vec![0u32; self.species.len()];
+        neighbors
+            .iter()
+            .flatten()
+            .filter(|neighbor| self.preys_on(neighbor.species, victim))
+            .for_each(|neighbor| damage[neighbor.species as usize] += self.attack(neighbor) as u32);
+
+        damage
+            .iter()
+            .enumerate()
+            .filter(|(_, damage)| **damage > 0)
+            .max_by_key(|(id, damage)| (**damage, std::cmp::Reverse(*id)))
+            .map(|(id, _)| id as SpeciesId)
+    }
+
+    fn preys_on(&self, attacker: SpeciesId, victim: SpeciesId) -> bool {
+        self.species[attacker as usize].preys_on(attacker, victim)
+    }
+
+    fn attack(&self, cell: &Cell) -> u8 {
+        self.species[cell.species as usize].attack_at(cell.age(self.tick))
+    }
+
+    // Damage from the safe zone and hazards, which nobody gets kills for
+    fn environment_damage(&self, x: usize, y: usize) -> u8 {
+        let zone = match self.zone {
+            Some((radius, damage))
+                if SafeZone::is_outside(radius, x, y, self.width, self.height) =>
+            {
+                damage
+            }
+            _ => 0,
+        };
+        let hazard = self.hazards.get(y * self.width + x).copied().unwrap_or(0);
+
+        zone.saturating_add(hazard)
+    }
+
+    // Three distinct parents have no majority; the pick then depends only on
+    // the position and tick so it's deterministic regardless of update order
+    fn majority_species(&self, parents: &[SpeciesId], x: usize, y: usize) -> SpeciesId {
+        for (i, species) in parents.iter().enumerate() {
+            if parents[i + 1..].contains(species) {
+                return *species;
+            }
+        }
+
+        let pick = (x as u64 * 31 + y as u64 * 17 + self.tick) as usize % parents.len();
+        parents[pick]
+    }
+}
+
+fn new_cell(species: &[Species], id: SpeciesId, tick: u64) -> Cell {
+    Cell {
+        species: id,
+        health: species[id as usize].health,
+        born: tick as u32,
+    }
+}
+
+#[rustfmt::skip]
+pub fn get_neighbors(ix: isize, iy: isize) -> [(isize, isize); 8] {
+    [
+        (ix - 1, iy - 1), (ix, iy - 1), (ix + 1, iy - 1),
+        (ix - 1, iy),                   (ix + 1, iy),
+        (ix - 1, iy + 1), (ix, iy + 1), (ix + 1, iy + 1),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populated(seed: u64) -> World {
+        let mut world = World::new(40, 30, seed);
+        world.populate(60);
+        world
+    }
+
+    #[test]
+    fn equal_seeds_give_equal_runs() {
+        let (mut a, mut b) = (populated(7), populated(7));
+        for _ in 0..50 {
+            a.step();
+            b.step();
+        }
+
+        assert!(a.population() > 0);
+        assert_eq!(a.current, b.current);
+        assert_eq!(a.previous, b.previous);
+        assert_eq!(a.kills, b.kills);
+        assert_eq!(a.deaths, b.deaths);
+    }
+
+    #[test]
+    fn different_seeds_give_different_worlds() {
+        assert_ne!(populated(7).current, populated(8).current);
+    }
+
+    #[test]
+    fn validate_rejects_inconsistent_worlds() {
+        assert!(populated(1).validate().is_ok());
+
+        let mut world = populated(1);
+        world.width = 0;
+        assert!(world.validate().is_err());
+
+        let mut world = populated(1);
+        world.previous.pop();
+        assert!(world.validate().is_err());
+
+        let mut world = populated(1);
+        world.walls = vec![false; 3];
+        assert!(world.validate().is_err());
+
+        let mut world = populated(1);
+        world.current[0] = Some(Cell {
+            species: 2,
+            health: 1,
+            born: 0,
+        });
+        assert!(world.validate().is_err());
+
+        let mut world = populated(1);
+        world.species[0].prey = Some(vec![5]);
+        assert!(world.validate().is_err());
+
+        let mut world = populated(1);
+        world.kills.push(0);
+        assert!(world.validate().is_err());
+    }
+}
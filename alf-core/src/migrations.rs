@@ -0,0 +1,549 @@
+//! Decoding of binary save payloads written with older layouts.
+//!
+//! Whenever the serialized layout of `World` changes, bump `SAVE_VERSION`,
+//! copy the old layout into this module as a private struct converting into
+//! `World`, turn the previous newest layout's conversion into one to the
+//! copy, and list it in `upgrade_through!` and `decode`. Layout changes of
+//! the cells and species a world holds count too. Timelines hold worlds as
+//! well, so the worlds in their snapshots have to be migrated alongside.
+//!
+//! Up to version 8 the payload was the world alone; since version 9 it's a
+//! `SaveFile`, which adds the timeline the world was saved with, and since
+//! version 10 the hashes for checking it resumes the same way.
+
+use crate::{Cell, Deaths, SafeZone, SaveFile, SimIoError, Species, Timeline, Topology, World};
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+
+/// Layout version written into new binary saves.
+pub const SAVE_VERSION: u32 = 10;
+
+pub fn decode(version: u32, payload: &[u8]) -> Result<SaveFile, SimIoError> {
+    match version {
+        1 => world::<WorldV1>(payload),
+        2 => world::<WorldV2>(payload),
+        3 => world::<WorldV3>(payload),
+        4 => world::<WorldV4>(payload),
+        5 => world::<WorldV5>(payload),
+        6 => world::<WorldV6>(payload),
+        7 => world::<WorldV7>(payload),
+        8 => world::<World>(payload),
+        9 => deserialize::<SaveFileV9>(payload).map(SaveFile::from),
+        SAVE_VERSION => deserialize(payload),
+        _ => Err(SimIoError::UnsupportedVersion(version)),
+    }
+}
+
+// A payload of just a world, saved before timelines were
+fn world<'a, T: Deserialize<'a> + Into<World>>(payload: &'a [u8]) -> Result<SaveFile, SimIoError> {
+    deserialize::<T>(payload).map(|world| SaveFile::from(world.into()))
+}
+
+// Older layouts reach the current one through each version in between
+macro_rules! upgrade_through {
+    ($($old:ident => $next:ident),*) => {$(
+        impl From<$old> for World {
+            fn from(world: $old) -> Self {
+                World::from($next::from(world))
+            }
+        }
+    )*};
+}
+
+upgrade_through!(
+    WorldV1 => WorldV2,
+    WorldV2 => WorldV3,
+    WorldV3 => WorldV4,
+    WorldV4 => WorldV5,
+    WorldV5 => WorldV6,
+    WorldV6 => WorldV7
+);
+
+fn deserialize<'a, T: Deserialize<'a>>(payload: &'a [u8]) -> Result<T, SimIoError> {
+    bincode::deserialize(payload).map_err(|e| SimIoError::Corrupt(e.to_string()))
+}
+
+/// Before saves held hashes to check resumes against.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct SaveFileV9 {
+    world: World,
+    timeline: Option<Timeline>,
+}
+
+impl From<SaveFileV9> for SaveFile {
+    fn from(save: SaveFileV9) -> Self {
+        SaveFile {
+            world: save.world,
+            timeline: save.timeline,
+            resume_hashes: vec![],
+        }
+    }
+}
+
+/// Before the safe zone.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct WorldV1 {
+    width: usize,
+    height: usize,
+    tick: u64,
+    species: Vec<SpeciesV1>,
+    current: Vec<Option<CellV1>>,
+    previous: Vec<Option<CellV1>>,
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl From<WorldV1> for WorldV2 {
+    fn from(world: WorldV1) -> Self {
+        WorldV2 {
+            width: world.width,
+            height: world.height,
+            tick: world.tick,
+            species: world.species,
+            current: world.current,
+            previous: world.previous,
+            seed: world.seed,
+            rng: world.rng,
+            safe_zone: None,
+        }
+    }
+}
+
+/// Before kills were counted.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct WorldV2 {
+    width: usize,
+    height: usize,
+    tick: u64,
+    species: Vec<SpeciesV1>,
+    current: Vec<Option<CellV1>>,
+    previous: Vec<Option<CellV1>>,
+    seed: u64,
+    rng: ChaCha8Rng,
+    safe_zone: Option<SafeZone>,
+}
+
+impl From<WorldV2> for WorldV3 {
+    fn from(world: WorldV2) -> Self {
+        WorldV3 {
+            width: world.width,
+            height: world.height,
+            tick: world.tick,
+            species: world.species,
+            current: world.current,
+            previous: world.previous,
+            seed: world.seed,
+            rng: world.rng,
+            safe_zone: world.safe_zone,
+            kills: vec![],
+        }
+    }
+}
+
+/// Before topologies, when every world was bounded.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct WorldV3 {
+    width: usize,
+    height: usize,
+    tick: u64,
+    species: Vec<SpeciesV1>,
+    current: Vec<Option<CellV1>>,
+    previous: Vec<Option<CellV1>>,
+    seed: u64,
+    rng: ChaCha8Rng,
+    safe_zone: Option<SafeZone>,
+    kills: Vec<u64>,
+}
+
+impl From<WorldV3> for WorldV4 {
+    fn from(world: WorldV3) -> Self {
+        WorldV4 {
+            width: world.width,
+            height: world.height,
+            tick: world.tick,
+            species: world.species,
+            current: world.current,
+            previous: world.previous,
+            seed: world.seed,
+            rng: world.rng,
+            safe_zone: world.safe_zone,
+            kills: world.kills,
+            topology: Topology::Bounded,
+        }
+    }
+}
+
+/// Before walls.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct WorldV4 {
+    width: usize,
+    height: usize,
+    tick: u64,
+    species: Vec<SpeciesV1>,
+    current: Vec<Option<CellV1>>,
+    previous: Vec<Option<CellV1>>,
+    seed: u64,
+    rng: ChaCha8Rng,
+    safe_zone: Option<SafeZone>,
+    kills: Vec<u64>,
+    topology: Topology,
+}
+
+impl From<WorldV4> for WorldV5 {
+    fn from(world: WorldV4) -> Self {
+        WorldV5 {
+            width: world.width,
+            height: world.height,
+            tick: world.tick,
+            species: world.species,
+            current: world.current,
+            previous: world.previous,
+            seed: world.seed,
+            rng: world.rng,
+            safe_zone: world.safe_zone,
+            kills: world.kills,
+            topology: world.topology,
+            walls: vec![],
+        }
+    }
+}
+
+/// Before hazards.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct WorldV5 {
+    width: usize,
+    height: usize,
+    tick: u64,
+    species: Vec<SpeciesV1>,
+    current: Vec<Option<CellV1>>,
+    previous: Vec<Option<CellV1>>,
+    seed: u64,
+    rng: ChaCha8Rng,
+    safe_zone: Option<SafeZone>,
+    kills: Vec<u64>,
+    topology: Topology,
+    walls: Vec<bool>,
+}
+
+impl From<WorldV5> for WorldV6 {
+    fn from(world: WorldV5) -> Self {
+        WorldV6 {
+            width: world.width,
+            height: world.height,
+            tick: world.tick,
+            species: world.species,
+            current: world.current,
+            previous: world.previous,
+            seed: world.seed,
+            rng: world.rng,
+            safe_zone: world.safe_zone,
+            kills: world.kills,
+            topology: world.topology,
+            walls: world.walls,
+            hazards: vec![],
+        }
+    }
+}
+
+/// Before cells aged and deaths were counted by cause.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct WorldV6 {
+    width: usize,
+    height: usize,
+    tick: u64,
+    species: Vec<SpeciesV1>,
+    current: Vec<Option<CellV1>>,
+    previous: Vec<Option<CellV1>>,
+    seed: u64,
+    rng: ChaCha8Rng,
+    safe_zone: Option<SafeZone>,
+    kills: Vec<u64>,
+    topology: Topology,
+    walls: Vec<bool>,
+    hazards: Vec<u8>,
+}
+
+// Cells alive when the save is loaded count as newborns
+impl From<WorldV6> for WorldV7 {
+    fn from(world: WorldV6) -> Self {
+        let cells = |cells: Vec<Option<CellV1>>| {
+            cells
+                .into_iter()
+                .map(|cell| cell.map(|cell| cell.born(world.tick)))
+                .collect()
+        };
+
+        WorldV7 {
+            width: world.width,
+            height: world.height,
+            tick: world.tick,
+            species: world.species.into_iter().map(SpeciesV2::from).collect(),
+            current: cells(world.current),
+            previous: cells(world.previous),
+            seed: world.seed,
+            rng: world.rng,
+            safe_zone: world.safe_zone,
+            kills: world.kills,
+            topology: world.topology,
+            walls: world.walls,
+            hazards: world.hazards,
+            deaths: vec![],
+        }
+    }
+}
+
+/// Before species chose their prey.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct WorldV7 {
+    width: usize,
+    height: usize,
+    tick: u64,
+    species: Vec<SpeciesV2>,
+    current: Vec<Option<Cell>>,
+    previous: Vec<Option<Cell>>,
+    seed: u64,
+    rng: ChaCha8Rng,
+    safe_zone: Option<SafeZone>,
+    kills: Vec<u64>,
+    topology: Topology,
+    walls: Vec<bool>,
+    hazards: Vec<u8>,
+    deaths: Vec<Deaths>,
+}
+
+impl From<WorldV7> for World {
+    fn from(world: WorldV7) -> Self {
+        World {
+            width: world.width,
+            height: world.height,
+            tick: world.tick,
+            species: world.species.into_iter().map(Species::from).collect(),
+            current: world.current,
+            previous: world.previous,
+            seed: world.seed,
+            rng: world.rng,
+            safe_zone: world.safe_zone,
+            kills: world.kills,
+            topology: world.topology,
+            walls: world.walls,
+            hazards: world.hazards,
+            deaths: world.deaths,
+        }
+    }
+}
+
+/// A cell before it knew its age.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct CellV1 {
+    species: u8,
+    health: u8,
+}
+
+impl CellV1 {
+    fn born(self, tick: u64) -> Cell {
+        Cell {
+            species: self.species,
+            health: self.health,
+            born: tick as u32,
+        }
+    }
+}
+
+/// A species before lifespans and veterans.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct SpeciesV1 {
+    name: String,
+    color: [u8; 4],
+    health: u8,
+    attack: u8,
+}
+
+impl From<SpeciesV1> for SpeciesV2 {
+    fn from(species: SpeciesV1) -> Self {
+        SpeciesV2 {
+            name: species.name,
+            color: species.color,
+            health: species.health,
+            attack: species.attack,
+            lifespan: None,
+            veteran_age: None,
+            veteran_attack: 0,
+        }
+    }
+}
+
+/// A species before it chose its prey, when it attacked every other one.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+struct SpeciesV2 {
+    name: String,
+    color: [u8; 4],
+    health: u8,
+    attack: u8,
+    lifespan: Option<u32>,
+    veteran_age: Option<u32>,
+    veteran_attack: u8,
+}
+
+impl From<SpeciesV2> for Species {
+    fn from(species: SpeciesV2) -> Self {
+        Species {
+            name: species.name,
+            color: species.color,
+            health: species.health,
+            attack: species.attack,
+            lifespan: species.lifespan,
+            veteran_age: species.veteran_age,
+            veteran_attack: species.veteran_attack,
+            prey: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    // Every fixture is a 2x2 world at tick 5 with a Blue cell of health 2
+    // in its top left corner
+    const TICK: u64 = 5;
+
+    fn fixture() -> WorldV1 {
+        let species = ["Red", "Blue"]
+            .into_iter()
+            .map(|name| SpeciesV1 {
+                name: name.to_string(),
+                color: [0, 0, 0, 255],
+                health: 3,
+                attack: 1,
+            })
+            .collect();
+        let cells = || {
+            let cell = CellV1 {
+                species: 1,
+                health: 2,
+            };
+            vec![Some(cell), None, None, None]
+        };
+
+        WorldV1 {
+            width: 2,
+            height: 2,
+            tick: TICK,
+            species,
+            current: cells(),
+            previous: cells(),
+            seed: 9,
+            rng: ChaCha8Rng::seed_from_u64(9),
+        }
+    }
+
+    // The fixture in every layout, indexed by version - 1. Each layout is
+    // made from the one before, setting the fields it added
+    fn payloads() -> Vec<Vec<u8>> {
+        fn encode(world: &impl serde::Serialize) -> Vec<u8> {
+            bincode::serialize(world).unwrap()
+        }
+
+        let v1 = fixture();
+        let mut payloads = vec![encode(&v1)];
+
+        let mut v2 = WorldV2::from(v1);
+        v2.safe_zone = Some(SafeZone::default());
+        payloads.push(encode(&v2));
+
+        let mut v3 = WorldV3::from(v2);
+        v3.kills = vec![4, 0];
+        payloads.push(encode(&v3));
+
+        let mut v4 = WorldV4::from(v3);
+        v4.topology = Topology::Torus;
+        payloads.push(encode(&v4));
+
+        let mut v5 = WorldV5::from(v4);
+        v5.walls = vec![false, false, false, true];
+        payloads.push(encode(&v5));
+
+        let mut v6 = WorldV6::from(v5);
+        v6.hazards = vec![0, 3, 0, 0];
+        payloads.push(encode(&v6));
+
+        let mut v7 = WorldV7::from(v6);
+        v7.deaths = vec![Deaths::default(), Deaths::default()];
+        v7.deaths[1].combat = 6;
+        for cells in [&mut v7.current, &mut v7.previous] {
+            cells[0].as_mut().unwrap().born = 1;
+        }
+        payloads.push(encode(&v7));
+
+        let v8 = World::from(v7);
+        payloads.push(encode(&v8));
+
+        let v9 = SaveFileV9 {
+            world: v8,
+            timeline: None,
+        };
+        payloads.push(encode(&v9));
+        assert_eq!(payloads.len(), SAVE_VERSION as usize - 1);
+        payloads
+    }
+
+    #[test]
+    fn old_layouts_migrate() {
+        for (index, payload) in payloads().iter().enumerate() {
+            let version = index as u32 + 1;
+            let save = decode(version, payload).unwrap();
+            assert!(save.timeline.is_none());
+            assert!(save.resume_hashes.is_empty());
+            let world = save.world;
+            world.validate().unwrap();
+
+            assert_eq!((world.width(), world.height(), world.tick()), (2, 2, TICK));
+            assert_eq!(world.species().len(), 2);
+            assert_eq!(world.species()[1].name, "Blue");
+            assert!(world.species()[0].preys_on(0, 1));
+            assert_eq!(world.population(), 1);
+            let cell = world.cell(0, 0).unwrap();
+            assert_eq!((cell.species, cell.health), (1, 2));
+
+            // What each layout added survives the later migrations
+            let since = |added: u32| version >= added;
+            assert_eq!(world.safe_zone().is_some(), since(2), "v{version}");
+            assert_eq!(world.kills(0), if since(3) { 4 } else { 0 }, "v{version}");
+            let topology = if since(4) {
+                Topology::Torus
+            } else {
+                Topology::Bounded
+            };
+            assert_eq!(world.topology(), topology, "v{version}");
+            assert_eq!(world.is_wall(1, 1), since(5), "v{version}");
+            assert_eq!(
+                world.hazard(1, 0),
+                if since(6) { 3 } else { 0 },
+                "v{version}"
+            );
+            let (deaths, age) = if since(7) { (6, 4) } else { (0, 0) };
+            assert_eq!(world.deaths(1).total(), deaths, "v{version}");
+            assert_eq!(cell.age(TICK), age, "v{version}");
+        }
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        assert!(matches!(
+            decode(SAVE_VERSION + 1, &[]),
+            Err(SimIoError::UnsupportedVersion(_))
+        ));
+    }
+}
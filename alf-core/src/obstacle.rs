@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// An impassable shape. Placing one in a world walls off every cell it
+/// covers.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape")]
+pub enum Obstacle {
+    /// A rectangle with its top left corner at `x`, `y`.
+    Rect {
+        x: isize,
+        y: isize,
+        width: usize,
+        height: usize,
+    },
+    /// A disc of cells whose centres are within `radius` of `x`, `y`.
+    Circle { x: isize, y: isize, radius: f32 },
+}
+
+impl Obstacle {
+    /// Coordinates of the cells the obstacle covers. They may lie outside
+    /// the world.
+    pub fn cells(&self) -> Vec<(isize, isize)> {
+        match *self {
+            Obstacle::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => (y..y + height as isize)
+                .flat_map(|cell_y| (x..x + width as isize).map(move |cell_x| (cell_x, cell_y)))
+                .collect(),
+            Obstacle::Circle { x, y, radius } => {
+                let reach = radius.max(0.0).floor() as isize;
+                (-reach..=reach)
+                    .flat_map(|dy| (-reach..=reach).map(move |dx| (dx, dy)))
+                    .filter(|(dx, dy)| ((dx * dx + dy * dy) as f32) <= radius * radius)
+                    .map(|(dx, dy)| (x + dx, y + dy))
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{SimIoError, SpeciesId, World};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+const MAGIC: &[u8; 4] = b"ALFR";
+// Version 1 held the replay as JSON, without edits
+const JSON_VERSION: u8 = 1;
+const VERSION: u8 = 2;
+
+/// A change made to the world from outside the simulation, such as with a
+/// tool. Replays record them so runs the player stepped into play back too.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Edit {
+    Spawn {
+        x: isize,
+        y: isize,
+        species: SpeciesId,
+    },
+    Kill {
+        x: isize,
+        y: isize,
+    },
+    Wall {
+        x: isize,
+        y: isize,
+        wall: bool,
+    },
+    Hazard {
+        x: isize,
+        y: isize,
+        damage: u8,
+    },
+    Prey {
+        attacker: SpeciesId,
+        victim: SpeciesId,
+        preys: bool,
+    },
+}
+
+impl Edit {
+    pub fn apply(&self, world: &mut World) {
+        match *self {
+            Edit::Spawn { x, y, species } => world.spawn(x, y, species),
+            Edit::Kill { x, y } => world.kill(x, y),
+            Edit::Wall { x, y, wall } => world.set_wall(x, y, wall),
+            Edit::Hazard { x, y, damage } => world.set_hazard(x, y, damage),
+            Edit::Prey {
+                attacker,
+                victim,
+                preys,
+            } => world.set_prey(attacker, victim, preys),
+        }
+    }
+
+    // Species the edit refers to, which must exist in the replayed world
+    fn species(&self) -> impl Iterator<Item = SpeciesId> {
+        let (first, second) = match *self {
+            Edit::Spawn { species, .. } => (Some(species), None),
+            Edit::Prey {
+                attacker, victim, ..
+            } => (Some(attacker), Some(victim)),
+            Edit::Kill { .. } | Edit::Wall { .. } | Edit::Hazard { .. } => (None, None),
+        };
+        first.into_iter().chain(second)
+    }
+}
+
+/// A recorded run: the world it started from, how many ticks it was stepped
+/// and the edits made along the way, each with the tick it was made at.
+/// Stepping is deterministic, so that's all playback needs.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub start: World,
+    pub ticks: u64,
+    #[serde(default)]
+    pub edits: Vec<(u64, Edit)>,
+}
+
+impl Replay {
+    pub fn new(start: &World) -> Self {
+        Self {
+            start: start.clone(),
+            ticks: 0,
+            edits: vec![],
+        }
+    }
+
+    pub fn record_tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    /// Records `edit` as made to the world at `tick`.
+    pub fn record_edit(&mut self, tick: u64, edit: Edit) {
+        self.edits.push((tick, edit));
+    }
+
+    /// Edits made while the world was at `tick`, in the order they were made.
+    pub fn edits_at(&self, tick: u64) -> impl Iterator<Item = &Edit> {
+        let start = self.edits.partition_point(|(at, _)| *at < tick);
+        self.edits[start..]
+            .iter()
+            .take_while(move |(at, _)| *at == tick)
+            .map(|(_, edit)| edit)
+    }
+
+    /// Tick the world reaches at the end of the replay.
+    pub fn end_tick(&self) -> u64 {
+        self.start.tick() + self.ticks
+    }
+
+    /// Encodes the replay as a `.alfr` file: magic bytes, a format version
+    /// and the deflated bincode encoded replay.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SimIoError> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+
+        let mut encoder = DeflateEncoder::new(bytes, Compression::default());
+        let payload = bincode::serialize(self).map_err(|e| SimIoError::Encode(e.to_string()))?;
+        encoder.write_all(&payload)?;
+        Ok(encoder.finish()?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SimIoError> {
+        let body = bytes.strip_prefix(MAGIC).ok_or(SimIoError::UnknownFormat)?;
+
+        let replay: Replay = match body.split_first() {
+            Some((&VERSION, compressed)) => {
+                let mut payload = vec![];
+                DeflateDecoder::new(compressed)
+                    .read_to_end(&mut payload)
+                    .map_err(|e| SimIoError::Corrupt(e.to_string()))?;
+                bincode::deserialize(&payload).map_err(|e| SimIoError::Corrupt(e.to_string()))?
+            }
+            Some((&JSON_VERSION, json)) => {
+                serde_json::from_slice(json).map_err(|e| SimIoError::Corrupt(e.to_string()))?
+            }
+            Some((&version, _)) => return Err(SimIoError::UnsupportedVersion(version.into())),
+            None => return Err(SimIoError::Truncated),
+        };
+        replay.validate()?;
+        Ok(replay)
+    }
+
+    // Playback looks edits up by tick and applies them to the start world,
+    // so they must be in order, within the run and name existing species
+    fn validate(&self) -> Result<(), SimIoError> {
+        self.start.validate()?;
+
+        let ticks = self.start.tick()..=self.end_tick();
+        let species = self.start.species().len();
+        let mut previous = self.start.tick();
+        for (tick, edit) in &self.edits {
+            if *tick < previous || !ticks.contains(tick) {
+                return Err(SimIoError::Corrupt(format!(
+                    "edit out of order at tick {tick}"
+                )));
+            }
+            if edit.species().any(|id| id as usize >= species) {
+                return Err(SimIoError::Corrupt(format!(
+                    "edit at tick {tick} refers to a species that doesn't exist"
+                )));
+            }
+            previous = *tick;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plays `replay` back the way the app does: edits made at a tick are
+    // applied once the world reaches it
+    fn play(replay: &Replay) -> World {
+        let mut world = replay.start.clone();
+        for edit in replay.edits_at(world.tick()) {
+            edit.apply(&mut world);
+        }
+        for _ in 0..replay.ticks {
+            world.step();
+            for edit in replay.edits_at(world.tick()) {
+                edit.apply(&mut world);
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn edited_runs_play_back() {
+        let mut world = World::new(30, 20, 7);
+        world.populate(20);
+        let mut replay = Replay::new(&world);
+
+        let edits = [
+            (
+                0,
+                Edit::Spawn {
+                    x: 3,
+                    y: 4,
+                    species: 1,
+                },
+            ),
+            (
+                2,
+                Edit::Wall {
+                    x: 10,
+                    y: 10,
+                    wall: true,
+                },
+            ),
+            (2, Edit::Kill { x: 5, y: 5 }),
+            (
+                5,
+                Edit::Hazard {
+                    x: 1,
+                    y: 1,
+                    damage: 2,
+                },
+            ),
+            (
+                5,
+                Edit::Prey {
+                    attacker: 0,
+                    victim: 1,
+                    preys: false,
+                },
+            ),
+        ];
+        for tick in 0..10 {
+            for (_, edit) in edits.iter().filter(|(at, _)| *at == tick) {
+                edit.apply(&mut world);
+                replay.record_edit(world.tick(), *edit);
+            }
+            world.step();
+            replay.record_tick();
+        }
+
+        let bytes = replay.to_bytes().unwrap();
+        let loaded = Replay::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.edits, replay.edits);
+        assert_eq!(
+            bincode::serialize(&play(&loaded)).unwrap(),
+            bincode::serialize(&world).unwrap()
+        );
+    }
+
+    #[test]
+    fn json_replays_still_load() {
+        let world = World::new(10, 10, 1);
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(JSON_VERSION);
+        let json = serde_json::json!({ "start": &world, "ticks": 4 });
+        bytes.extend(serde_json::to_vec(&json).unwrap());
+
+        let replay = Replay::from_bytes(&bytes).unwrap();
+        assert_eq!(replay.end_tick(), 4);
+        assert!(replay.edits.is_empty());
+    }
+
+    #[test]
+    fn edits_for_missing_species_are_rejected() {
+        let mut replay = Replay::new(&World::new(10, 10, 1));
+        replay.record_tick();
+        replay.record_edit(
+            1,
+            Edit::Spawn {
+                x: 0,
+                y: 0,
+                species: 9,
+            },
+        );
+
+        let bytes = replay.to_bytes().unwrap();
+        assert!(matches!(
+            Replay::from_bytes(&bytes),
+            Err(SimIoError::Corrupt(_))
+        ));
+    }
+}
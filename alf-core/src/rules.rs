@@ -0,0 +1,159 @@
+use crate::{SpeciesId, World};
+use serde::{Deserialize, Serialize};
+
+/// When a match is over. Every enabled condition is checked each tick and
+/// the first one met ends the match.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchRules {
+    /// End once a single species has live cells left.
+    pub last_species_standing: bool,
+    /// End once a species has killed this many enemy cells.
+    pub kill_limit: Option<u64>,
+    /// End after this many ticks, won by the species with the most cells.
+    pub time_limit: Option<u64>,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            last_species_standing: true,
+            kill_limit: None,
+            time_limit: None,
+        }
+    }
+}
+
+/// How a match ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchEnd {
+    /// Every other species was wiped out.
+    LastStanding(SpeciesId),
+    /// The species reached the kill limit first.
+    KillLimit(SpeciesId),
+    /// Time ran out; `None` when the largest species are tied.
+    TimeLimit(Option<SpeciesId>),
+    /// Every cell died.
+    Extinction,
+}
+
+impl MatchEnd {
+    pub fn winner(&self) -> Option<SpeciesId> {
+        match *self {
+            MatchEnd::LastStanding(id) | MatchEnd::KillLimit(id) => Some(id),
+            MatchEnd::TimeLimit(winner) => winner,
+            MatchEnd::Extinction => None,
+        }
+    }
+}
+
+impl MatchRules {
+    /// Checks whether the match played out in `world` is over.
+    pub fn evaluate(&self, world: &World) -> Option<MatchEnd> {
+        let populations = world.populations();
+        if populations.iter().all(|population| *population == 0) {
+            return Some(MatchEnd::Extinction);
+        }
+
+        if self.last_species_standing {
+            if let Some(winner) = world.winner() {
+                return Some(MatchEnd::LastStanding(winner));
+            }
+        }
+
+        if let Some(limit) = self.kill_limit {
+            let leader = (0..world.species().len() as SpeciesId)
+                .find(|species| world.kills(*species) >= limit);
+            if let Some(leader) = leader {
+                return Some(MatchEnd::KillLimit(leader));
+            }
+        }
+
+        match self.time_limit {
+            Some(limit) if world.tick() >= limit => {
+                let largest = populations.iter().max().copied().unwrap_or_default();
+                let mut leaders = populations
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, population)| **population == largest);
+
+                let winner = match (leaders.next(), leaders.next()) {
+                    (Some((id, _)), None) => Some(id as SpeciesId),
+                    _ => None,
+                };
+                Some(MatchEnd::TimeLimit(winner))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world(cells: &[SpeciesId]) -> World {
+        let mut world = World::new(10, 10, 0);
+        for (x, species) in cells.iter().enumerate() {
+            world.spawn(x as _, 0, *species);
+        }
+        world
+    }
+
+    fn no_limits() -> MatchRules {
+        MatchRules {
+            last_species_standing: false,
+            ..MatchRules::default()
+        }
+    }
+
+    #[test]
+    fn extinction() {
+        assert_eq!(
+            MatchRules::default().evaluate(&world(&[])),
+            Some(MatchEnd::Extinction)
+        );
+    }
+
+    #[test]
+    fn last_species_standing() {
+        let rules = MatchRules::default();
+        assert_eq!(rules.evaluate(&world(&[0, 1])), None);
+        assert_eq!(
+            rules.evaluate(&world(&[1, 1])),
+            Some(MatchEnd::LastStanding(1))
+        );
+        assert_eq!(no_limits().evaluate(&world(&[1, 1])), None);
+    }
+
+    #[test]
+    fn kill_limit() {
+        let rules = MatchRules {
+            kill_limit: Some(3),
+            ..no_limits()
+        };
+        let mut world = world(&[0, 1]);
+        world.kills = vec![2, 2];
+        assert_eq!(rules.evaluate(&world), None);
+
+        world.kills = vec![2, 3];
+        assert_eq!(rules.evaluate(&world), Some(MatchEnd::KillLimit(1)));
+    }
+
+    #[test]
+    fn time_limit() {
+        let rules = MatchRules {
+            time_limit: Some(10),
+            ..no_limits()
+        };
+        let mut world = world(&[0, 1, 1]);
+        world.tick = 9;
+        assert_eq!(rules.evaluate(&world), None);
+
+        world.tick = 10;
+        assert_eq!(rules.evaluate(&world), Some(MatchEnd::TimeLimit(Some(1))));
+
+        world.current[2] = None;
+        assert_eq!(rules.evaluate(&world), Some(MatchEnd::TimeLimit(None)));
+    }
+}
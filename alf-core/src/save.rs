@@ -0,0 +1,337 @@
+use crate::migrations::{self, SAVE_VERSION};
+use crate::{SimIoError, Timeline, World};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::Path;
+
+// Binary saves: magic, little-endian u32 layout version, deflated bincode
+const MAGIC: &[u8; 4] = b"ALFZ";
+// Uncompressed, unversioned binary saves from before versioning, which hold
+// the version 1 layout
+const LEGACY_MAGIC: &[u8; 4] = b"ALFS";
+
+/// Ticks past the saved one whose state hashes a save holds, so loading it
+/// can check the world carries on the same way.
+pub const RESUME_TICKS: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SaveFormat {
+    /// Human-readable, but large and slow for big worlds.
+    Json,
+    /// Compressed, versioned bincode encoding.
+    Binary,
+}
+
+impl SaveFormat {
+    /// `.json` files are saved as JSON, anything else in the binary format.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => SaveFormat::Json,
+            _ => SaveFormat::Binary,
+        }
+    }
+}
+
+/// What a save holds: the world, the timeline of branches that led to it if
+/// it was saved with one, and hashes for checking it resumes as it would
+/// have.
+///
+/// Headless runs write the world wrapped together with their summary, so
+/// JSON saves are accepted either bare or inside an object with a `world`
+/// field.
+#[derive(Deserialize)]
+pub struct SaveFile {
+    pub world: World,
+    #[serde(default)]
+    pub timeline: Option<Timeline>,
+    /// State hashes of the world over the ticks following the save. Empty
+    /// in saves from before they were stored.
+    #[serde(default)]
+    pub resume_hashes: Vec<u64>,
+}
+
+/// Whether a loaded world steps the way it would have had it never been
+/// saved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResumeCheck {
+    /// The save has no hashes to check against.
+    Unchecked,
+    Verified,
+    /// The world first differs from the saved run at this tick, so the save
+    /// left out something stepping depends on.
+    Diverged(u64),
+}
+
+impl SaveFile {
+    /// Steps a copy of the world through the ticks the save holds hashes
+    /// for and compares them.
+    pub fn check_resume(&self) -> ResumeCheck {
+        if self.resume_hashes.is_empty() {
+            return ResumeCheck::Unchecked;
+        }
+
+        let hashes = self.world.future_hashes(self.resume_hashes.len());
+        match hashes
+            .iter()
+            .zip(&self.resume_hashes)
+            .position(|(a, b)| a != b)
+        {
+            Some(index) => ResumeCheck::Diverged(self.world.tick() + index as u64 + 1),
+            None => ResumeCheck::Verified,
+        }
+    }
+}
+
+impl From<World> for SaveFile {
+    fn from(world: World) -> Self {
+        Self {
+            world,
+            timeline: None,
+            resume_hashes: vec![],
+        }
+    }
+}
+
+// Serializes like `SaveFile` without taking ownership
+#[derive(Serialize)]
+struct SaveRef<'a> {
+    world: &'a World,
+    timeline: Option<&'a Timeline>,
+    resume_hashes: Vec<u64>,
+}
+
+pub fn save_world(
+    world: &World,
+    timeline: Option<&Timeline>,
+    format: SaveFormat,
+) -> Result<Vec<u8>, SimIoError> {
+    let save = SaveRef {
+        world,
+        timeline,
+        resume_hashes: world.future_hashes(RESUME_TICKS),
+    };
+    match format {
+        SaveFormat::Json => {
+            serde_json::to_vec(&save).map_err(|e| SimIoError::Encode(e.to_string()))
+        }
+        SaveFormat::Binary => {
+            let mut bytes = MAGIC.to_vec();
+            bytes.extend_from_slice(&SAVE_VERSION.to_le_bytes());
+
+            let mut encoder = DeflateEncoder::new(bytes, Compression::default());
+            let payload =
+                bincode::serialize(&save).map_err(|e| SimIoError::Encode(e.to_string()))?;
+            encoder.write_all(&payload)?;
+            Ok(encoder.finish()?)
+        }
+    }
+}
+
+/// Loads a world saved in any supported format, telling them apart by the
+/// magic bytes at the start of the binary formats. Binary saves written with
+/// an older layout are migrated. Saves that decode but don't hold together
+/// are rejected as corrupt.
+pub fn load_world(bytes: &[u8]) -> Result<SaveFile, SimIoError> {
+    let save = decode_save(bytes)?;
+    save.world.validate()?;
+    if let Some(timeline) = &save.timeline {
+        timeline.validate()?;
+    }
+    Ok(save)
+}
+
+fn decode_save(bytes: &[u8]) -> Result<SaveFile, SimIoError> {
+    if let Some(body) = bytes.strip_prefix(MAGIC) {
+        let (version, compressed) = body.split_first_chunk::<4>().ok_or(SimIoError::Truncated)?;
+
+        let mut payload = vec![];
+        DeflateDecoder::new(compressed)
+            .read_to_end(&mut payload)
+            .map_err(|e| SimIoError::Corrupt(e.to_string()))?;
+
+        return migrations::decode(u32::from_le_bytes(*version), &payload);
+    }
+
+    if let Some(body) = bytes.strip_prefix(LEGACY_MAGIC) {
+        return migrations::decode(1, body);
+    }
+
+    serde_json::from_slice::<World>(bytes)
+        .map(SaveFile::from)
+        .or_else(|e| serde_json::from_slice::<SaveFile>(bytes).map_err(|_| e))
+        .map_err(|e| match e.classify() {
+            serde_json::error::Category::Syntax => SimIoError::UnknownFormat,
+            serde_json::error::Category::Eof => SimIoError::Truncated,
+            _ => SimIoError::Corrupt(e.to_string()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SafeZone, Topology};
+
+    fn world() -> World {
+        let mut world = World::new(20, 10, 3);
+        world.populate(10);
+        world.set_wall(0, 0, true);
+        world.set_hazard(1, 1, 2);
+        for _ in 0..5 {
+            world.step();
+        }
+        world
+    }
+
+    fn assert_same(a: &World, b: &World) {
+        assert_eq!(
+            bincode::serialize(a).unwrap(),
+            bincode::serialize(b).unwrap()
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let world = world();
+        let bytes = save_world(&world, None, SaveFormat::Json).unwrap();
+        assert_same(&load_world(&bytes).unwrap().world, &world);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let world = world();
+        let bytes = save_world(&world, None, SaveFormat::Binary).unwrap();
+        assert!(bytes.starts_with(MAGIC));
+        assert_same(&load_world(&bytes).unwrap().world, &world);
+    }
+
+    #[test]
+    fn wrapped_json_loads() {
+        let world = world();
+        let bytes = serde_json::to_vec(&world).unwrap();
+        assert_same(&load_world(&bytes).unwrap().world, &world);
+
+        let bytes = serde_json::to_vec(&serde_json::json!({ "world": &world })).unwrap();
+        assert_same(&load_world(&bytes).unwrap().world, &world);
+    }
+
+    // A main branch and a branch forked from it at its second snapshot
+    fn timeline(world: &World) -> Timeline {
+        let mut timeline = Timeline::new(100, usize::MAX, 3);
+        let mut world = world.clone();
+        for _ in 0..6 {
+            timeline.push(&world);
+            world.step();
+        }
+        timeline.fork(1);
+        world.spawn(0, 1, 1);
+        timeline.push_keyframe(&world);
+        timeline
+    }
+
+    #[test]
+    fn timelines_are_saved() {
+        let world = world();
+        for format in [SaveFormat::Json, SaveFormat::Binary] {
+            let saved = timeline(&world);
+            let bytes = save_world(&world, Some(&saved), format).unwrap();
+            let loaded = load_world(&bytes).unwrap().timeline.unwrap();
+
+            assert_eq!(loaded.active(), 1);
+            assert_eq!(loaded.branches().len(), 2);
+            let fork_tick = saved.branches()[0].history.get(1).unwrap().tick();
+            assert_eq!(loaded.branches()[1].parent, Some((0, fork_tick)));
+            for (saved, loaded) in saved.branches().iter().zip(loaded.branches()) {
+                assert_eq!(saved.history.len(), loaded.history.len());
+                for index in 0..saved.history.len() {
+                    assert_same(
+                        &loaded.history.get(index).unwrap(),
+                        &saved.history.get(index).unwrap(),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn loads_resume_like_uninterrupted_runs() {
+        let world = world();
+        let mut uninterrupted = world.clone();
+        for _ in 0..RESUME_TICKS {
+            uninterrupted.step();
+        }
+
+        for format in [SaveFormat::Json, SaveFormat::Binary] {
+            let bytes = save_world(&world, None, format).unwrap();
+            let save = load_world(&bytes).unwrap();
+            assert_eq!(save.check_resume(), ResumeCheck::Verified);
+
+            let mut resumed = save.world;
+            for _ in 0..RESUME_TICKS {
+                resumed.step();
+            }
+            assert_same(&resumed, &uninterrupted);
+        }
+    }
+
+    #[test]
+    fn dropped_fields_are_caught() {
+        let mut world = World::new(30, 20, 4);
+        world.set_topology(Topology::Torus);
+        world.set_safe_zone(Some(SafeZone {
+            start_tick: 0,
+            shrink_ticks: 40,
+            ..SafeZone::default()
+        }));
+        world.set_wall(3, 3, true);
+        world.set_hazard(10, 10, 2);
+        world.populate(40);
+        for _ in 0..20 {
+            world.step();
+        }
+        assert!(world.kills(0) + world.kills(1) > 0);
+
+        let bytes = save_world(&world, None, SaveFormat::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        // Fields that load with a default when missing, as a field the save
+        // forgot to write would
+        for field in [
+            "safe_zone",
+            "kills",
+            "topology",
+            "walls",
+            "hazards",
+            "deaths",
+        ] {
+            let mut json = json.clone();
+            json["world"].as_object_mut().unwrap().remove(field);
+            let save = load_world(&serde_json::to_vec(&json).unwrap()).unwrap();
+            assert_eq!(
+                save.check_resume(),
+                ResumeCheck::Diverged(world.tick() + 1),
+                "{field}"
+            );
+        }
+
+        assert_eq!(SaveFile::from(world).check_resume(), ResumeCheck::Unchecked);
+    }
+
+    #[test]
+    fn bad_files_are_errors() {
+        assert!(matches!(
+            load_world(b"not a save"),
+            Err(SimIoError::UnknownFormat)
+        ));
+        assert!(matches!(
+            load_world(b"ALFZ\x08"),
+            Err(SimIoError::Truncated)
+        ));
+
+        let mut json = serde_json::to_value(world()).unwrap();
+        json["width"] = 0.into();
+        let bytes = serde_json::to_vec(&json).unwrap();
+        assert!(matches!(load_world(&bytes), Err(SimIoError::Corrupt(_))));
+    }
+}
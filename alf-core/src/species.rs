@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// Index of a species in the world's species registry.
+pub type SpeciesId = u8;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Species {
+    pub name: String,
+    /// RGBA color used to draw the species' cells.
+    pub color: [u8; 4],
+    /// Health a cell is born with and regenerates to when not under attack.
+    pub health: u8,
+    /// Damage dealt each tick to every neighbouring cell of another species.
+    pub attack: u8,
+    /// Ticks a cell lives before dying of old age, or forever if unset.
+    #[serde(default)]
+    pub lifespan: Option<u32>,
+    /// Age in ticks from which a cell attacks with `veteran_attack` instead.
+    #[serde(default)]
+    pub veteran_age: Option<u32>,
+    #[serde(default)]
+    pub veteran_attack: u8,
+    /// Species this one attacks, or every other one if unset.
+    #[serde(default)]
+    pub prey: Option<Vec<SpeciesId>>,
+}
+
+impl Species {
+    pub fn new(name: &str, color: [u8; 4]) -> Self {
+        Self {
+            name: name.to_string(),
+            color,
+            health: 3,
+            attack: 1,
+            lifespan: None,
+            veteran_age: None,
+            veteran_attack: 0,
+            prey: None,
+        }
+    }
+
+    /// Attack of a cell that has lived for `age` ticks.
+    pub fn attack_at(&self, age: u32) -> u8 {
+        match self.veteran_age {
+            Some(veteran_age) if age >= veteran_age => self.veteran_attack,
+            _ => self.attack,
+        }
+    }
+
+    /// Whether cells of this species attack cells of `victim`. Nothing
+    /// attacks its own species, so `own` is the id of this one.
+    pub fn preys_on(&self, own: SpeciesId, victim: SpeciesId) -> bool {
+        own != victim && self.prey.as_ref().is_none_or(|prey| prey.contains(&victim))
+    }
+
+    /// Whether a cell of this species `age` ticks old has outlived its lifespan.
+    pub fn is_too_old(&self, age: u32) -> bool {
+        self.lifespan.is_some_and(|lifespan| age >= lifespan)
+    }
+
+    /// The two teams a world starts with unless told otherwise.
+    pub fn defaults() -> Vec<Species> {
+        vec![
+            Species::new("Red", [255, 0, 0, 255]),
+            Species::new("Blue", [0, 0, 255, 255]),
+        ]
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{History, SimIoError, World};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct Branch {
+    pub name: String,
+    /// Branch and tick this branch was forked from.
+    pub parent: Option<(usize, u64)>,
+    pub history: History,
+}
+
+/// Tree of alternative histories. Forking from a past snapshot starts a new
+/// branch there and leaves the future of the original branch untouched.
+///
+/// The snapshot and memory caps hold for every branch together: once
+/// they're exceeded the oldest snapshot of any branch is evicted, though
+/// each branch keeps its newest one.
+#[derive(Serialize, Deserialize)]
+pub struct Timeline {
+    branches: Vec<Branch>,
+    active: usize,
+    // Caps come from the config, not from saves
+    #[serde(skip, default = "unlimited")]
+    max_snapshots: usize,
+    #[serde(skip, default = "unlimited")]
+    max_bytes: usize,
+}
+
+fn unlimited() -> usize {
+    usize::MAX
+}
+
+impl Timeline {
+    pub fn new(max_snapshots: usize, max_bytes: usize, keyframe_interval: usize) -> Self {
+        // Branches are capped by the timeline as a whole
+        let history = History::new(usize::MAX, usize::MAX, keyframe_interval);
+        Self {
+            branches: vec![Branch {
+                name: "main".to_string(),
+                parent: None,
+                history,
+            }],
+            active: 0,
+            max_snapshots: max_snapshots.max(1),
+            max_bytes,
+        }
+    }
+
+    pub fn branches(&self) -> &[Branch] {
+        &self.branches
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn history(&self) -> &History {
+        &self.branches[self.active].history
+    }
+
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.branches[self.active].history
+    }
+
+    /// Snapshots `world` into the active branch, evicting old snapshots to
+    /// stay within the caps.
+    pub fn push(&mut self, world: &World) {
+        self.history_mut().push(world);
+        self.evict();
+    }
+
+    /// Stores `world` in full in the active branch, as `History::push_keyframe`.
+    pub fn push_keyframe(&mut self, world: &World) {
+        self.history_mut().push_keyframe(world);
+        self.evict();
+    }
+
+    /// Starts a new branch from snapshot `index` of the active branch and
+    /// makes it active. Returns its id, or `None` if there's no such snapshot.
+    pub fn fork(&mut self, index: usize) -> Option<usize> {
+        let parent = &self.branches[self.active];
+        let world = parent.history.get(index)?;
+
+        let mut history = parent.history.empty_like();
+        history.push_keyframe(&world);
+
+        self.branches.push(Branch {
+            name: format!("branch {}", self.branches.len()),
+            parent: Some((self.active, world.tick())),
+            history,
+        });
+        self.active = self.branches.len() - 1;
+        self.evict();
+
+        Some(self.active)
+    }
+
+    /// Makes `branch` active and returns the newest state recorded in it.
+    pub fn switch_to(&mut self, branch: usize) -> Option<World> {
+        if branch >= self.branches.len() {
+            return None;
+        }
+
+        self.active = branch;
+        self.history().latest().cloned()
+    }
+
+    /// Drops every branch and starts over with an empty main history.
+    pub fn clear(&mut self) {
+        let history = self.history().empty_like();
+        self.branches = vec![Branch {
+            name: "main".to_string(),
+            parent: None,
+            history,
+        }];
+        self.active = 0;
+    }
+
+    /// Takes over the branches of a loaded timeline, keeping this one's
+    /// caps.
+    pub fn restore(&mut self, loaded: Timeline) {
+        self.branches = loaded.branches;
+        self.active = loaded.active;
+        self.evict();
+    }
+
+    /// Checks that a timeline read from a file is consistent, so browsing
+    /// it can't panic.
+    pub fn validate(&self) -> Result<(), SimIoError> {
+        let corrupt = |message: &str| Err(SimIoError::Corrupt(message.to_string()));
+
+        if self.active >= self.branches.len() {
+            return corrupt("the active branch doesn't exist");
+        }
+
+        for branch in &self.branches {
+            if branch
+                .parent
+                .is_some_and(|(parent, _)| parent >= self.branches.len())
+            {
+                return corrupt("a branch was forked from a branch that doesn't exist");
+            }
+            branch.history.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Estimated memory held by the snapshots of every branch.
+    pub fn bytes(&self) -> usize {
+        self.branches
+            .iter()
+            .map(|branch| branch.history.bytes())
+            .sum()
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    // Evicts the oldest snapshot of any branch until the caps are met or
+    // every branch is down to its newest snapshot
+    fn evict(&mut self) {
+        loop {
+            let snapshots: usize = self.branches.iter().map(|b| b.history.len()).sum();
+            if snapshots <= self.max_snapshots && self.bytes() <= self.max_bytes {
+                return;
+            }
+
+            let oldest = self
+                .branches
+                .iter_mut()
+                .filter(|branch| branch.history.len() > 1)
+                .min_by_key(|branch| branch.history.first_tick());
+            match oldest {
+                Some(branch) => branch.history.evict_oldest(),
+                None => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshots(timeline: &Timeline) -> usize {
+        timeline.branches().iter().map(|b| b.history.len()).sum()
+    }
+
+    #[test]
+    fn caps_hold_across_branches() {
+        let mut timeline = Timeline::new(10, usize::MAX, 3);
+        let mut world = World::new(10, 10, 2);
+        world.populate(10);
+        for _ in 0..8 {
+            timeline.push(&world);
+            world.step();
+        }
+
+        let fork_tick = timeline.history().get(5).unwrap().tick();
+        timeline.fork(5).unwrap();
+        for _ in 0..8 {
+            world.step();
+            timeline.push(&world);
+        }
+
+        assert_eq!(snapshots(&timeline), 10);
+        assert!(timeline.branches().iter().all(|b| !b.history.is_empty()));
+        // The fork point is still named right after the main branch lost
+        // the snapshot it was forked from
+        assert!(timeline.branches()[0].history.first_tick() > Some(fork_tick));
+        assert_eq!(timeline.branches()[1].parent, Some((0, fork_tick)));
+    }
+
+    #[test]
+    fn every_branch_keeps_its_newest_snapshot() {
+        let mut timeline = Timeline::new(1, 0, 1);
+        let world = World::new(10, 10, 2);
+        timeline.push(&world);
+        timeline.push(&world);
+        timeline.fork(0).unwrap();
+
+        assert_eq!(snapshots(&timeline), 2);
+    }
+
+    #[test]
+    fn restore_applies_the_caps() {
+        let mut loaded = Timeline::new(usize::MAX, usize::MAX, 3);
+        let mut world = World::new(10, 10, 2);
+        for _ in 0..20 {
+            loaded.push(&world);
+            world.step();
+        }
+
+        let mut timeline = Timeline::new(5, usize::MAX, 3);
+        timeline.restore(loaded);
+        assert_eq!(snapshots(&timeline), 5);
+        timeline.validate().unwrap();
+    }
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// What lies past the edges of the world.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Topology {
+    /// Nothing, so cells along the edges have fewer neighbours.
+    #[default]
+    Bounded,
+    /// The edges wrap around to the opposite side.
+    Torus,
+}
+
+impl Topology {
+    /// Index of the cell at `x`, `y` in a row-major grid, or `None` when
+    /// that's off a bounded grid.
+    pub(crate) fn index(self, width: usize, height: usize, x: isize, y: isize) -> Option<usize> {
+        let (x, y) = match self {
+            Topology::Bounded => {
+                if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+                    return None;
+                }
+                (x as usize, y as usize)
+            }
+            Topology::Torus => (
+                x.rem_euclid(width as isize) as usize,
+                y.rem_euclid(height as isize) as usize,
+            ),
+        };
+
+        Some((y * width) + x)
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+/// A circular safe zone centred on the world that shrinks as a match goes
+/// on. Cells outside it take damage every tick and can't heal.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafeZone {
+    /// Tick the zone starts shrinking at. Until then it covers the whole
+    /// world.
+    pub start_tick: u64,
+    /// Ticks the zone takes to shrink to its final size.
+    pub shrink_ticks: u64,
+    /// Final radius as a fraction of half the world's shorter side.
+    pub final_radius: f32,
+    /// Damage dealt each tick to every cell outside the zone.
+    pub damage: u8,
+}
+
+impl Default for SafeZone {
+    fn default() -> Self {
+        Self {
+            start_tick: 200,
+            shrink_ticks: 1000,
+            final_radius: 0.25,
+            damage: 1,
+        }
+    }
+}
+
+impl SafeZone {
+    /// Radius of the zone in cells at `tick`, in a world of the given size.
+    pub fn radius(&self, tick: u64, width: usize, height: usize) -> f32 {
+        let (width, height) = (width as f32, height as f32);
+        // `hypot` may round differently between platforms, unlike `sqrt`
+        let full = (width * width + height * height).sqrt() / 2.0;
+        let end = self.final_radius * width.min(height) / 2.0;
+
+        let elapsed = tick.saturating_sub(self.start_tick) as f32;
+        let progress = (elapsed / self.shrink_ticks.max(1) as f32).min(1.0);
+        full + (end - full) * progress
+    }
+
+    /// Whether the cell at `x`, `y` is outside a zone of `radius` cells.
+    pub fn is_outside(radius: f32, x: usize, y: usize, width: usize, height: usize) -> bool {
+        let dx = x as f32 + 0.5 - width as f32 / 2.0;
+        let dy = y as f32 + 0.5 - height as f32 / 2.0;
+        dx * dx + dy * dy > radius * radius
+    }
+}
@@ -0,0 +1,144 @@
+//! Runs canonical worlds from fixed seeds and compares where they end up
+//! with the golden values in `goldens.json`, so changes to how worlds step
+//! can't go unnoticed. After a deliberate change, regenerate them with:
+//!
+//! ```text
+//! UPDATE_GOLDENS=1 cargo test -p alf-core --test goldens
+//! ```
+
+use alf_core::{SafeZone, Species, Topology, World};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const TICKS: u64 = 300;
+
+// Metric names and their values, for each scenario
+type Goldens = BTreeMap<String, BTreeMap<String, u64>>;
+
+// A name and the world it starts from
+type Scenario = (&'static str, fn() -> World);
+
+const SCENARIOS: [Scenario; 3] = [
+    ("conway", conway),
+    ("predator prey", predator_prey),
+    ("fight", fight),
+];
+
+/// Two teams fighting by Conway's rules from random clusters.
+fn conway() -> World {
+    let mut world = World::new(64, 48, 1);
+    world.populate(120);
+    world
+}
+
+/// Three species each hunting the next, with limited lifespans.
+fn predator_prey() -> World {
+    let species = ["Fox", "Rabbit", "Grass"]
+        .into_iter()
+        .enumerate()
+        .map(|(id, name)| Species {
+            lifespan: Some(40 + id as u32 * 20),
+            prey: Some(vec![(id as u8 + 1) % 3]),
+            ..Species::new(name, [255; 4])
+        })
+        .collect();
+    let mut world = World::with_species(64, 48, 2, species);
+    world.set_topology(Topology::Torus);
+    world.populate(150);
+    world
+}
+
+/// A walled arena with hazards and a safe zone shrinking over the run.
+fn fight() -> World {
+    let mut world = World::new(64, 48, 3);
+    world.enclose(2, 2, 60, 44);
+    for x in 20..44 {
+        world.set_hazard(x, 24, 1);
+    }
+    world.set_safe_zone(Some(SafeZone {
+        start_tick: 50,
+        shrink_ticks: 200,
+        ..SafeZone::default()
+    }));
+    world.populate(120);
+    world
+}
+
+fn measure(mut world: World) -> BTreeMap<String, u64> {
+    for _ in 0..TICKS {
+        world.step();
+    }
+
+    let mut metrics = BTreeMap::from([
+        ("state hash".to_string(), world.state_hash()),
+        ("tick".to_string(), world.tick()),
+    ]);
+    let populations = world.populations();
+    for (id, species) in world.species().iter().enumerate() {
+        let deaths = world.deaths(id as u8);
+        let name = &species.name;
+        metrics.insert(format!("{name} population"), populations[id] as u64);
+        metrics.insert(format!("{name} kills"), world.kills(id as u8));
+        metrics.insert(format!("{name} combat deaths"), deaths.combat);
+        metrics.insert(format!("{name} environment deaths"), deaths.environment);
+        metrics.insert(format!("{name} neighbour deaths"), deaths.neighbours);
+        metrics.insert(format!("{name} old age deaths"), deaths.old_age);
+    }
+    metrics
+}
+
+fn goldens_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/goldens.json")
+}
+
+#[test]
+fn canonical_runs_match_goldens() {
+    let measured: Goldens = SCENARIOS
+        .into_iter()
+        .map(|(name, world)| (name.to_string(), measure(world())))
+        .collect();
+
+    let path = goldens_path();
+    if std::env::var_os("UPDATE_GOLDENS").is_some() {
+        let json = serde_json::to_string_pretty(&measured).unwrap();
+        std::fs::write(&path, json + "\n").unwrap();
+        return;
+    }
+
+    let goldens: Goldens = std::fs::read(&path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_else(|| {
+            panic!(
+                "cannot read {}, set UPDATE_GOLDENS to write it",
+                path.display()
+            )
+        });
+
+    let mut mismatches = vec![];
+    for (scenario, metrics) in &measured {
+        for (metric, new) in metrics {
+            let old = goldens.get(scenario).and_then(|golden| golden.get(metric));
+            if old != Some(new) {
+                let old = old.map_or("missing".to_string(), u64::to_string);
+                mismatches.push(format!("{scenario}: {metric} was {old}, now {new}"));
+            }
+        }
+    }
+    for (scenario, metrics) in &goldens {
+        for metric in metrics.keys() {
+            if !measured
+                .get(scenario)
+                .is_some_and(|m| m.contains_key(metric))
+            {
+                mismatches.push(format!("{scenario}: {metric} is no longer measured"));
+            }
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "runs changed from the goldens, set UPDATE_GOLDENS if that's intended:\n{}",
+        mismatches.join("\n")
+    );
+}
@@ -0,0 +1,91 @@
+//! Periodic autosaves into rotating slots, and detection of sessions that
+//! ended without a clean exit so the latest autosave can be offered back.
+
+use alf_core::{SaveFormat, SimIoError, Timeline, World};
+use notan::utils::{Duration, Instant};
+use std::path::{Path, PathBuf};
+
+// Exists while a session is running; finding it at startup means the
+// previous session crashed
+const SESSION_MARKER: &str = "alf.running";
+
+pub struct Autosave {
+    /// Ticks between autosaves, 0 disables autosaving
+    pub interval: u64,
+    /// Time between autosaves, if they're also made on a timer
+    every: Option<Duration>,
+    last_save: Instant,
+    slots: usize,
+    next_slot: usize,
+}
+
+impl Autosave {
+    /// `minutes` adds a timer on top of the tick interval, 0 leaves it off.
+    pub fn new(interval: u64, minutes: u64, slots: usize) -> Self {
+        let slots = slots.max(1);
+
+        // Carry on after the newest slot so it's the last to be overwritten
+        let next_slot = latest_slot(slots).map_or(0, |slot| (slot + 1) % slots);
+
+        Self {
+            interval,
+            every: (minutes > 0).then(|| Duration::from_secs(minutes * 60)),
+            last_save: Instant::now(),
+            slots,
+            next_slot,
+        }
+    }
+
+    pub fn is_due(&self, tick: u64) -> bool {
+        let ticks_due = self.interval > 0 && tick.is_multiple_of(self.interval);
+        let time_due = self
+            .every
+            .is_some_and(|every| self.last_save.elapsed() >= every);
+        ticks_due || time_due
+    }
+
+    /// Writes `world` and its timeline over the oldest slot and returns its
+    /// path.
+    pub fn save(&mut self, world: &World, timeline: &Timeline) -> Result<PathBuf, SimIoError> {
+        let path = slot_path(self.next_slot);
+        std::fs::write(
+            &path,
+            alf_core::save_world(world, Some(timeline), SaveFormat::Binary)?,
+        )?;
+
+        self.last_save = Instant::now();
+        self.next_slot = (self.next_slot + 1) % self.slots;
+        Ok(path)
+    }
+
+    /// Path of the most recently written autosave, if there is one.
+    pub fn latest(&self) -> Option<PathBuf> {
+        latest_slot(self.slots).map(slot_path)
+    }
+}
+
+fn slot_path(slot: usize) -> PathBuf {
+    PathBuf::from(format!("autosave_{slot}.alfs"))
+}
+
+fn latest_slot(slots: usize) -> Option<usize> {
+    (0..slots)
+        .filter_map(|slot| {
+            let modified = std::fs::metadata(slot_path(slot)).ok()?.modified().ok()?;
+            Some((modified, slot))
+        })
+        .max()
+        .map(|(_, slot)| slot)
+}
+
+/// Marks a session as running. Returns whether the previous session crashed
+/// instead of exiting through `end_session`.
+pub fn begin_session() -> bool {
+    let crashed = Path::new(SESSION_MARKER).exists();
+    let _ = std::fs::write(SESSION_MARKER, "");
+    crashed
+}
+
+pub fn end_session() {
+    let _ = std::fs::remove_file(SESSION_MARKER);
+}
@@ -0,0 +1,236 @@
+//! Where the world is drawn in the window and how close up.
+
+use crate::input::{Action, Bindings};
+use alf_core::World;
+use notan::prelude::*;
+
+// Zoom range relative to fitting the whole world in the window
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 32.0;
+// Zoom per notch of the mouse wheel, which notan reports as 50
+const ZOOM_PER_NOTCH: f32 = 1.2;
+// How quickly the view catches up with where it's headed, and how quickly
+// a flung view slows down, per second
+const SMOOTHING: f32 = 12.0;
+const FRICTION: f32 = 4.0;
+// Window pixels per second the pan keys move the view
+const PAN_SPEED: f32 = 600.0;
+// Flings slower than this many cells per second stop dead
+const MIN_SPEED: f32 = 0.5;
+
+pub struct Camera {
+    // What's shown, easing toward `target`
+    view: View,
+    target: View,
+    // Cells per second the target drifts after a drag is let go
+    velocity: (f32, f32),
+    // Mouse position when the middle button drag was last seen
+    dragged_from: Option<(f32, f32)>,
+}
+
+#[derive(Clone, Copy)]
+struct View {
+    // Cell coordinates shown at the middle of the window
+    center: (f32, f32),
+    // Magnification over fitting the whole world in the window
+    zoom: f32,
+}
+
+impl View {
+    fn scale(&self, size: (f32, f32), world: &World) -> f32 {
+        let (width, height) = size;
+        let fit = (width / world.width() as f32).min(height / world.height() as f32);
+        fit * self.zoom
+    }
+
+    fn origin(&self, size: (f32, f32), world: &World) -> (f32, f32) {
+        let scale = self.scale(size, world);
+        (
+            size.0 / 2.0 - self.center.0 * scale,
+            size.1 / 2.0 - self.center.1 * scale,
+        )
+    }
+
+    fn cell_at(&self, point: (f32, f32), size: (f32, f32), world: &World) -> (f32, f32) {
+        let scale = self.scale(size, world);
+        let (x, y) = self.origin(size, world);
+        ((point.0 - x) / scale, (point.1 - y) / scale)
+    }
+}
+
+impl Camera {
+    /// A camera showing all of `world`.
+    pub fn fit(world: &World) -> Self {
+        let view = View {
+            center: (world.width() as f32 / 2.0, world.height() as f32 / 2.0),
+            zoom: 1.0,
+        };
+        Self {
+            view,
+            target: view,
+            velocity: (0.0, 0.0),
+            dragged_from: None,
+        }
+    }
+
+    /// Zooms with the mouse wheel and the zoom keys, pans with the pan
+    /// keys and by dragging, and flings the view when a drag is let go.
+    /// The mouse is ignored while `over_ui`.
+    pub fn control(&mut self, app: &mut App, world: &World, keys: &Bindings, over_ui: bool) {
+        let size = window_size(app.window().size());
+        let cursor = app.mouse.position();
+        let dt = app.timer.delta_f32();
+
+        let notches = app.mouse.wheel_delta.y / 50.0;
+        if notches != 0.0 && !over_ui {
+            self.zoom_at(notches, cursor, size, world);
+        }
+
+        // Keys zoom about the middle of the window
+        let middle = (size.0 / 2.0, size.1 / 2.0);
+        if keys.was_pressed(app, Action::ZoomIn) {
+            self.zoom_at(1.0, middle, size, world);
+        }
+        if keys.was_pressed(app, Action::ZoomOut) {
+            self.zoom_at(-1.0, middle, size, world);
+        }
+
+        let axis = |negative, positive| {
+            keys.is_down(app, positive) as i8 as f32 - keys.is_down(app, negative) as i8 as f32
+        };
+        let direction = (
+            axis(Action::PanLeft, Action::PanRight),
+            axis(Action::PanUp, Action::PanDown),
+        );
+        if direction != (0.0, 0.0) {
+            self.steer(direction, dt, size, world);
+        }
+
+        if keys.is_down(app, Action::DragView) {
+            if let Some(from) = self.dragged_from {
+                let delta = (cursor.0 - from.0, cursor.1 - from.1);
+                self.pan(delta, size, world);
+
+                // Remember how fast the world was moving to fling it
+                if dt > 0.0 {
+                    let scale = self.view.scale(size, world);
+                    self.velocity = (-delta.0 / scale / dt, -delta.1 / scale / dt);
+                }
+            }
+            // Drags have to start on the world, not the UI
+            if self.dragged_from.is_some() || !over_ui {
+                self.dragged_from = Some(cursor);
+            }
+        } else {
+            self.dragged_from = None;
+        }
+
+        if keys.was_pressed(app, Action::FitView) {
+            self.fit_view(world);
+        }
+    }
+
+    /// Eases the view `dt` seconds further toward where it's headed. With
+    /// `clamp` the middle of the view stays over the world.
+    pub fn update(&mut self, dt: f32, world: &World, clamp: bool) {
+        if self.dragged_from.is_none() {
+            self.target.center.0 += self.velocity.0 * dt;
+            self.target.center.1 += self.velocity.1 * dt;
+
+            let decay = (-FRICTION * dt).exp();
+            self.velocity = (self.velocity.0 * decay, self.velocity.1 * decay);
+            if self.velocity.0.hypot(self.velocity.1) < MIN_SPEED {
+                self.velocity = (0.0, 0.0);
+            }
+        }
+
+        if clamp {
+            let (width, height) = (world.width() as f32, world.height() as f32);
+            self.target.center.0 = self.target.center.0.clamp(0.0, width);
+            self.target.center.1 = self.target.center.1.clamp(0.0, height);
+        }
+
+        // Zoom eases by ratio so zooming in and out feel the same
+        let t = 1.0 - (-SMOOTHING * dt).exp();
+        let (view, target) = (&mut self.view, &self.target);
+        view.center.0 += (target.center.0 - view.center.0) * t;
+        view.center.1 += (target.center.1 - view.center.1) * t;
+        view.zoom *= (target.zoom / view.zoom).powf(t);
+    }
+
+    /// Pixels per cell in a window of `size`. Cells are square whatever
+    /// the window's shape.
+    pub fn scale(&self, size: (f32, f32), world: &World) -> f32 {
+        self.view.scale(size, world)
+    }
+
+    /// Window position of the world's top left corner.
+    pub fn origin(&self, size: (f32, f32), world: &World) -> (f32, f32) {
+        self.view.origin(size, world)
+    }
+
+    /// Cell coordinates under the window position `point`.
+    pub fn cell_at(&self, point: (f32, f32), size: (f32, f32), world: &World) -> (f32, f32) {
+        self.view.cell_at(point, size, world)
+    }
+
+    /// Whether the view is closer than fitting the whole world.
+    pub fn is_zoomed_in(&self) -> bool {
+        self.view.zoom > MIN_ZOOM + 0.01
+    }
+
+    /// Heads smoothly toward showing `cell` in the middle of the window.
+    pub fn center_on(&mut self, cell: (f32, f32)) {
+        self.target.center = cell;
+        self.velocity = (0.0, 0.0);
+    }
+
+    /// Heads smoothly back to showing all of `world`.
+    pub fn fit_view(&mut self, world: &World) {
+        self.target = Self::fit(world).target;
+        self.velocity = (0.0, 0.0);
+    }
+
+    /// Moves the view for `dt` seconds in `direction`, whose components
+    /// run from -1 to 1, as the pan keys or a stick do.
+    pub fn steer(&mut self, direction: (f32, f32), dt: f32, size: (f32, f32), world: &World) {
+        let step = PAN_SPEED * dt / self.target.scale(size, world);
+        self.target.center.0 += direction.0 * step;
+        self.target.center.1 += direction.1 * step;
+        self.velocity = (0.0, 0.0);
+    }
+
+    /// Zooms by the mouse wheel's `notches`, keeping the cell under
+    /// `cursor` in place once the view settles.
+    pub fn zoom_at(&mut self, notches: f32, cursor: (f32, f32), size: (f32, f32), world: &World) {
+        let target = &mut self.target;
+        let before = target.cell_at(cursor, size, world);
+        target.zoom = (target.zoom * ZOOM_PER_NOTCH.powf(notches)).clamp(MIN_ZOOM, MAX_ZOOM);
+        let after = target.cell_at(cursor, size, world);
+        target.center.0 += before.0 - after.0;
+        target.center.1 += before.1 - after.1;
+    }
+
+    /// Zooms by `factor` about `center` at once, without easing, as when
+    /// pinching the world.
+    #[cfg(target_arch = "wasm32")]
+    pub fn pinch(&mut self, factor: f32, center: (f32, f32), size: (f32, f32), world: &World) {
+        let notches = factor.ln() / ZOOM_PER_NOTCH.ln();
+        self.zoom_at(notches, center, size, world);
+        self.view = self.target;
+    }
+
+    /// Moves the view by `delta` window pixels, as when dragging the world.
+    /// The view follows the mouse without easing.
+    pub fn pan(&mut self, delta: (f32, f32), size: (f32, f32), world: &World) {
+        let scale = self.view.scale(size, world);
+        for view in [&mut self.view, &mut self.target] {
+            view.center.0 -= delta.0 / scale;
+            view.center.1 -= delta.1 / scale;
+        }
+    }
+}
+
+pub fn window_size((width, height): (i32, i32)) -> (f32, f32) {
+    (width as f32, height as f32)
+}
@@ -0,0 +1,92 @@
+//! What cells are drawn in: the attribute live cells are colored by, the
+//! color scale for attributes that vary continuously, and walls and hazards.
+
+use alf_core::{Species, World};
+use notan::prelude::Color;
+
+pub const WALL_COLOR: Color = Color::new(0.25, 0.25, 0.3, 1.0);
+pub const HAZARD_COLOR: Color = Color::new(0.85, 0.55, 0.9, 1.0);
+
+/// Attribute live cells are colored by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorBy {
+    Species,
+    /// Health as a share of the species' full health.
+    Health,
+    /// Age relative to the oldest live cell.
+    Age,
+}
+
+impl ColorBy {
+    pub const ALL: [ColorBy; 3] = [ColorBy::Species, ColorBy::Health, ColorBy::Age];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorBy::Species => "Species",
+            ColorBy::Health => "Health",
+            ColorBy::Age => "Age",
+        }
+    }
+}
+
+/// Blue at 0 through green and yellow to red at 1.
+pub fn gradient(t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let (r, g, b) = if t < 0.5 {
+        (0.0, t * 2.0, 1.0 - t * 2.0)
+    } else {
+        (1.0, 2.0 - t * 2.0, 0.0)
+    };
+    Color::new(r, g, b, 1.0)
+}
+
+pub fn species_color(species: &Species) -> Color {
+    let [r, g, b, a] = species.color;
+    Color::from_bytes(r, g, b, a)
+}
+
+/// Age of the oldest live cell, if there are any.
+pub fn oldest(world: &World) -> Option<u32> {
+    (0..world.height())
+        .flat_map(|y| (0..world.width()).map(move |x| (x, y)))
+        .filter_map(|(x, y)| world.cell(x, y))
+        .map(|cell| cell.age(world.tick()))
+        .max()
+}
+
+/// Colors the cells of one world are drawn in.
+pub struct Palette {
+    color_by: ColorBy,
+    // Ages are scaled to the oldest cell
+    oldest: u32,
+}
+
+impl Palette {
+    pub fn new(world: &World, color_by: ColorBy) -> Self {
+        let oldest = match color_by {
+            ColorBy::Age => oldest(world).unwrap_or(0),
+            _ => 0,
+        };
+        Self { color_by, oldest }
+    }
+
+    /// Color of the cell, or `None` if it's empty.
+    pub fn color(&self, world: &World, x: usize, y: usize) -> Option<Color> {
+        match world.cell(x, y) {
+            Some(cell) => {
+                let species = &world.species()[cell.species as usize];
+                let color = match self.color_by {
+                    ColorBy::Species => species_color(species),
+                    ColorBy::Health => gradient(cell.health as f32 / species.health.max(1) as f32),
+                    ColorBy::Age => {
+                        gradient(cell.age(world.tick()) as f32 / self.oldest.max(1) as f32)
+                    }
+                };
+                Some(color)
+            }
+            None if world.is_wall(x, y) => Some(WALL_COLOR),
+            None if world.hazard(x, y) > 0 => Some(HAZARD_COLOR),
+            None => None,
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! Simulation and display parameters read from `alf.toml` at startup.
+//! Fields missing from the file keep their defaults, and a template with
+//! every default is written when there's no config file yet.
+//!
+//! Scenarios are config files too, usually with cells, walls and hazards
+//! laid out in them.
+
+use crate::files;
+use crate::input::Bindings;
+use alf_core::{MatchRules, Obstacle, SafeZone, SimIoError, Species, SpeciesId, Topology, World};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const CONFIG_PATH: &str = "alf.toml";
+
+/// Longest side a world may have, which keeps a typo in the config from
+/// allocating gigabytes.
+const MAX_WORLD_SIDE: usize = 4096;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// What the scenario is about, shown when hovering a built-in one in the
+    /// Scenarios menu.
+    pub description: Option<String>,
+    /// World size in cells. New worlds pick up changes on reload.
+    pub width: usize,
+    pub height: usize,
+    /// `Bounded`, or `Torus` for a world whose edges wrap around.
+    pub topology: Topology,
+    /// An `[arena]` table confines new worlds to a rectangle by walling off
+    /// everything outside it.
+    pub arena: Option<Arena>,
+    /// Impassable shapes placed in new worlds, each an `[[obstacles]]`
+    /// table with `shape = "Rect"` and `x`, `y`, `width`, `height`, or
+    /// `shape = "Circle"` and `x`, `y`, `radius`.
+    pub obstacles: Vec<Obstacle>,
+    /// Number of clusters a new world is seeded with.
+    pub initial_clusters: usize,
+    pub ticks_per_second: f32,
+    /// Number of steps a dead cell takes to fade back to the background.
+    pub death_fade_steps: u8,
+    /// The world is snapshotted into the rewind history every this many
+    /// ticks.
+    pub snapshot_interval: u64,
+    /// History limits only apply at startup.
+    pub history_max_snapshots: usize,
+    pub history_max_mib: usize,
+    /// Every this many snapshots one is stored in full, the rest as deltas.
+    pub history_keyframe_interval: usize,
+    /// The density heatmap is recomputed every this many ticks, in square
+    /// blocks of `heatmap_block` cells a side.
+    pub heatmap_interval: u64,
+    pub heatmap_block: usize,
+    /// A `[keys]` table rebinds actions, such as `pause = { Key = "P" }`
+    /// or `use_tool = { Mouse = "Right" }`.
+    pub keys: Bindings,
+    /// Keeps the middle of the view over the world when panning.
+    pub camera_clamp: bool,
+    /// Most particles shown at once; the oldest make way for new ones.
+    pub particle_budget: usize,
+    /// Checks that loaded saves step the way the run they were saved from
+    /// did, by stepping a copy for a few ticks.
+    pub verify_loads: bool,
+    /// Pixels per cell in screenshots.
+    pub screenshot_scale: u32,
+    /// A `[screenshot_area]` table, laid out like `[arena]`, limits
+    /// screenshots to that rectangle of cells.
+    pub screenshot_area: Option<Arena>,
+    /// Teams new worlds start with.
+    pub species: Vec<Species>,
+    /// Battle royale mode: a `[safe_zone]` table gives new worlds a
+    /// shrinking safe zone.
+    pub safe_zone: Option<SafeZone>,
+    /// When a match is over, checked every tick.
+    pub rules: MatchRules,
+    /// Cells new worlds start with on top of the clusters, each as
+    /// `[x, y, species]`.
+    pub cells: Vec<(isize, isize, SpeciesId)>,
+    /// Wall cells of new worlds, each as `[x, y]`.
+    pub walls: Vec<(isize, isize)>,
+    /// Hazards of new worlds, each as `[x, y, damage]`.
+    pub hazards: Vec<(isize, isize, u8)>,
+}
+
+/// Rectangle of cells, such as the one the fight is confined to.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Arena {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Arena {
+    fn fits(&self, width: usize, height: usize) -> bool {
+        let right = self.x.checked_add(self.width);
+        let bottom = self.y.checked_add(self.height);
+        right.is_some_and(|right| right <= width) && bottom.is_some_and(|bottom| bottom <= height)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            description: None,
+            width: 100,
+            height: 100,
+            topology: Topology::default(),
+            arena: None,
+            obstacles: vec![],
+            initial_clusters: 500,
+            ticks_per_second: 20.0,
+            death_fade_steps: 6,
+            snapshot_interval: 10,
+            history_max_snapshots: 5000,
+            history_max_mib: 64,
+            history_keyframe_interval: 30,
+            heatmap_interval: 10,
+            heatmap_block: 8,
+            keys: Bindings::default(),
+            camera_clamp: true,
+            particle_budget: 2000,
+            verify_loads: true,
+            screenshot_scale: 8,
+            screenshot_area: None,
+            species: Species::defaults(),
+            safe_zone: None,
+            rules: MatchRules::default(),
+            cells: vec![],
+            walls: vec![],
+            hazards: vec![],
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config at `path`, writing the defaults there first if it
+    /// doesn't exist yet. On the web it lives in localStorage, like saves.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = match files::read(path) {
+            Ok(bytes) => bytes,
+            Err(SimIoError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                let config = Config::default();
+                if let Ok(template) = toml::to_string_pretty(&config) {
+                    let _ = files::write(path, template.as_bytes());
+                }
+                return Ok(config);
+            }
+            Err(err) => return Err(format!("Cannot read {}: {err}", path.display())),
+        };
+
+        let text = String::from_utf8(bytes)
+            .map_err(|_| format!("Cannot read {}: not UTF-8 text", path.display()))?;
+        Self::parse(&text, &path.display().to_string())
+    }
+
+    /// Writes the config to `path`, replacing what was there.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        files::write(path, text.as_bytes())
+            .map_err(|e| format!("Cannot write {}: {e}", path.display()))
+    }
+
+    /// Reads a config from the TOML `text`, naming it `source` in errors.
+    pub fn parse(text: &str, source: &str) -> Result<Self, String> {
+        let config: Config =
+            toml::from_str(text).map_err(|e| format!("Cannot parse {source}: {e}"))?;
+        config
+            .validate()
+            .map_err(|e| format!("Invalid {source}: {e}"))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err("the world needs a width and height of at least 1".to_string());
+        }
+
+        if self.width > MAX_WORLD_SIDE || self.height > MAX_WORLD_SIDE {
+            return Err(format!(
+                "the world can be at most {MAX_WORLD_SIDE} cells wide and high"
+            ));
+        }
+
+        for (name, area) in [
+            ("arena", self.arena),
+            ("screenshot_area", self.screenshot_area),
+        ] {
+            if area.is_some_and(|area| !area.fits(self.width, self.height)) {
+                return Err(format!("the {name} doesn't fit in the world"));
+            }
+        }
+
+        // Obstacles may hang over the edges, but no further than the
+        // world's size, so walking their cells can't overflow
+        let side = MAX_WORLD_SIDE as isize;
+        let near = |x: isize, y: isize| x.abs() <= side && y.abs() <= side;
+        let misplaced = self.obstacles.iter().any(|obstacle| match *obstacle {
+            Obstacle::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => !near(x, y) || width > MAX_WORLD_SIDE || height > MAX_WORLD_SIDE,
+            Obstacle::Circle { x, y, radius } => {
+                !near(x, y) || !(0.0..=MAX_WORLD_SIDE as f32).contains(&radius)
+            }
+        });
+        if misplaced {
+            return Err("an obstacle lies too far outside the world".to_string());
+        }
+
+        if self.species.is_empty() || self.species.len() > SpeciesId::MAX as usize + 1 {
+            return Err(format!(
+                "there must be between 1 and {} species",
+                SpeciesId::MAX as usize + 1
+            ));
+        }
+
+        if let Some(species) = self
+            .species
+            .iter()
+            .find(|species| species.lifespan == Some(0))
+        {
+            return Err(format!("{} needs a lifespan of at least 1", species.name));
+        }
+
+        // A missing veteran_attack reads as 0, which would leave veterans
+        // harmless
+        if let Some(species) = self
+            .species
+            .iter()
+            .find(|species| species.veteran_age.is_some() && species.veteran_attack == 0)
+        {
+            return Err(format!(
+                "{} has a veteran_age, so it needs a veteran_attack of at least 1",
+                species.name
+            ));
+        }
+
+        let species_count = self.species.len();
+        for species in &self.species {
+            let prey = species.prey.iter().flatten();
+            if let Some(id) = prey.copied().find(|id| *id as usize >= species_count) {
+                return Err(format!(
+                    "{} preys on species {id}, which doesn't exist",
+                    species.name
+                ));
+            }
+        }
+
+        if let Some((_, _, id)) = self
+            .cells
+            .iter()
+            .find(|(_, _, id)| *id as usize >= species_count)
+        {
+            return Err(format!(
+                "a cell belongs to species {id}, which doesn't exist"
+            ));
+        }
+
+        if !self.ticks_per_second.is_finite() || self.ticks_per_second <= 0.0 {
+            return Err("ticks_per_second must be a positive number".to_string());
+        }
+
+        if self.snapshot_interval == 0 {
+            return Err("snapshot_interval must be at least 1".to_string());
+        }
+
+        if self.heatmap_interval == 0 || self.heatmap_block == 0 {
+            return Err("heatmap_interval and heatmap_block must be at least 1".to_string());
+        }
+
+        if self.history_keyframe_interval == 0 {
+            return Err("history_keyframe_interval must be at least 1".to_string());
+        }
+
+        if self.screenshot_scale == 0 {
+            return Err("screenshot_scale must be at least 1".to_string());
+        }
+
+        if let Some(zone) = &self.safe_zone {
+            if zone.shrink_ticks == 0 {
+                return Err("safe_zone.shrink_ticks must be at least 1".to_string());
+            }
+            if !(0.0..=1.0).contains(&zone.final_radius) {
+                return Err("safe_zone.final_radius must be between 0 and 1".to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Seconds between ticks.
+    pub fn step_size(&self) -> f32 {
+        1.0 / self.ticks_per_second
+    }
+
+    /// A new world populated as configured.
+    pub fn new_world(&self, seed: u64) -> alf_core::World {
+        let mut world =
+            alf_core::World::with_species(self.width, self.height, seed, self.species.clone());
+        // Clusters placed on an edge wrap around on a torus
+        world.set_topology(self.topology);
+        world.set_safe_zone(self.safe_zone);
+        if let Some(arena) = self.arena {
+            world.enclose(arena.x, arena.y, arena.width, arena.height);
+        }
+        for obstacle in &self.obstacles {
+            world.place_obstacle(obstacle);
+        }
+        for &(x, y) in &self.walls {
+            world.set_wall(x, y, true);
+        }
+        for &(x, y, damage) in &self.hazards {
+            world.set_hazard(x, y, damage);
+        }
+        world.populate(self.initial_clusters);
+        for &(x, y, species) in &self.cells {
+            world.spawn(x, y, species);
+        }
+        world
+    }
+
+    /// This config with `world` as it is now as the layout new worlds start
+    /// from, for saving as a scenario. Key bindings are left out, since
+    /// they belong to whoever plays it.
+    pub fn capture(&self, world: &World) -> Config {
+        let mut cells = vec![];
+        let mut walls = vec![];
+        let mut hazards = vec![];
+        for y in 0..world.height() {
+            for x in 0..world.width() {
+                let (cx, cy) = (x as isize, y as isize);
+                if let Some(cell) = world.cell(x, y) {
+                    cells.push((cx, cy, cell.species));
+                }
+                if world.is_wall(x, y) {
+                    walls.push((cx, cy));
+                }
+                if world.hazard(x, y) > 0 {
+                    hazards.push((cx, cy, world.hazard(x, y)));
+                }
+            }
+        }
+
+        Config {
+            width: world.width(),
+            height: world.height(),
+            topology: world.topology(),
+            arena: None,
+            obstacles: vec![],
+            initial_clusters: 0,
+            species: world.species().to_vec(),
+            safe_zone: world.safe_zone().copied(),
+            cells,
+            walls,
+            hazards,
+            keys: Bindings::default(),
+            ..self.clone()
+        }
+    }
+}
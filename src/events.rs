@@ -0,0 +1,140 @@
+//! Log of notable things that happened in the run, kept for browsing.
+
+use alf_core::{Deaths, World};
+use std::collections::VecDeque;
+
+// Events kept; older ones are dropped
+const MAX_EVENTS: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    Kill,
+    /// Deaths to hazards, the safe zone or old age. Deaths by Conway's
+    /// rules happen every tick and are only charted.
+    Death,
+    Extinction,
+    Edit,
+    MatchEnd,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 5] = [
+        EventKind::Kill,
+        EventKind::Death,
+        EventKind::Extinction,
+        EventKind::Edit,
+        EventKind::MatchEnd,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::Kill => "Kills",
+            EventKind::Death => "Deaths",
+            EventKind::Extinction => "Extinctions",
+            EventKind::Edit => "Edits",
+            EventKind::MatchEnd => "Match end",
+        }
+    }
+}
+
+pub struct Event {
+    pub tick: u64,
+    pub kind: EventKind,
+    pub text: String,
+}
+
+// Counts events are derived from by comparing them between ticks
+struct Totals {
+    kills: Vec<u64>,
+    deaths: Vec<Deaths>,
+    populations: Vec<usize>,
+}
+
+impl Totals {
+    fn of(world: &World) -> Self {
+        let ids = 0..world.species().len() as u8;
+        Self {
+            kills: ids.clone().map(|id| world.kills(id)).collect(),
+            deaths: ids.map(|id| world.deaths(id)).collect(),
+            populations: world.populations(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct EventLog {
+    events: VecDeque<Event>,
+    last: Option<Totals>,
+}
+
+impl EventLog {
+    pub fn events(&self) -> &VecDeque<Event> {
+        &self.events
+    }
+
+    /// Forgets every event and starts following `world`.
+    pub fn reset(&mut self, world: &World) {
+        self.events.clear();
+        self.rebase(world);
+    }
+
+    /// Makes `world` what later changes are measured from, without logging
+    /// anything for how it differs from before.
+    pub fn rebase(&mut self, world: &World) {
+        self.last = Some(Totals::of(world));
+    }
+
+    pub fn push(&mut self, tick: u64, kind: EventKind, text: String) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(Event { tick, kind, text });
+    }
+
+    /// Logs what changed since the world was last observed, one event per
+    /// species and kind.
+    pub fn observe(&mut self, world: &World) {
+        let totals = Totals::of(world);
+        let tick = world.tick();
+
+        if let Some(last) = self.last.take() {
+            for (id, species) in world.species().iter().enumerate() {
+                let name = &species.name;
+                let kills = since(&last.kills, &totals.kills, id, |kills| *kills);
+                if kills > 0 {
+                    self.push(
+                        tick,
+                        EventKind::Kill,
+                        format!("{name} killed {kills} cells"),
+                    );
+                }
+
+                let environment = since(&last.deaths, &totals.deaths, id, |d| d.environment);
+                if environment > 0 {
+                    let text = format!("{environment} {name} cells died in hazards or the zone");
+                    self.push(tick, EventKind::Death, text);
+                }
+
+                let old_age = since(&last.deaths, &totals.deaths, id, |d| d.old_age);
+                if old_age > 0 {
+                    let text = format!("{old_age} {name} cells died of old age");
+                    self.push(tick, EventKind::Death, text);
+                }
+
+                let was_alive = last.populations.get(id).is_some_and(|n| *n > 0);
+                if was_alive && totals.populations[id] == 0 {
+                    self.push(tick, EventKind::Extinction, format!("{name} died out"));
+                }
+            }
+        }
+
+        self.last = Some(totals);
+    }
+}
+
+// Growth of a running total of species `id`. Totals shrink after a rewind,
+// which counts as nothing happening
+fn since<T>(last: &[T], now: &[T], id: usize, count: impl Fn(&T) -> u64) -> u64 {
+    let last = last.get(id).map_or(0, &count);
+    now.get(id).map_or(0, &count).saturating_sub(last)
+}
@@ -0,0 +1,226 @@
+//! Where saves, replays, the config and the recent files list live: on disk
+//! on desktop, in the browser's localStorage on the web. Also native file
+//! dialogs on desktop and file download and upload on the web.
+
+use crate::{SAVE_PATH, SCENARIO_PATH};
+#[cfg(not(target_arch = "wasm32"))]
+use alf_core::SimIoError;
+use std::path::{Path, PathBuf};
+
+const RECENT_FILES_PATH: &str = "alf.recent";
+const MAX_RECENT_FILES: usize = 8;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(path: &Path) -> Result<Vec<u8>, SimIoError> {
+    Ok(std::fs::read(path)?)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(path: &Path, bytes: &[u8]) -> Result<(), SimIoError> {
+    Ok(std::fs::write(path, bytes)?)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new()
+        .add_filter("World", &["alfs"])
+        .add_filter("JSON world", &["json"])
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick_save_path() -> Option<PathBuf> {
+    dialog().set_file_name(SAVE_PATH).save_file()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick_load_path() -> Option<PathBuf> {
+    dialog().pick_file()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn scenario_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new().add_filter("Scenario", &["toml"])
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick_scenario_save_path() -> Option<PathBuf> {
+    scenario_dialog().set_file_name(SCENARIO_PATH).save_file()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick_scenario_load_path() -> Option<PathBuf> {
+    scenario_dialog().pick_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use web::*;
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use super::{SAVE_PATH, SCENARIO_PATH};
+    use alf_core::SimIoError;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use std::cell::RefCell;
+    use std::io::{Error, ErrorKind};
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen::JsCast;
+    use web_sys::{Blob, FileReader, HtmlAnchorElement, HtmlInputElement, Storage, Url};
+
+    /// Contents of a file the user is uploading, filled in once the browser
+    /// has read it.
+    pub type Upload = Rc<RefCell<Option<Vec<u8>>>>;
+
+    fn storage() -> Result<Storage, SimIoError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| Error::new(ErrorKind::Unsupported, "localStorage is unavailable").into())
+    }
+
+    // localStorage only holds strings, so files are stored base64 encoded
+    // under their path
+    fn key(path: &Path) -> String {
+        format!("alf:{}", path.display())
+    }
+
+    fn js_error(err: JsValue) -> SimIoError {
+        Error::other(format!("{err:?}")).into()
+    }
+
+    pub fn read(path: &Path) -> Result<Vec<u8>, SimIoError> {
+        let encoded = storage()?
+            .get_item(&key(path))
+            .map_err(js_error)?
+            .ok_or_else(|| Error::from(ErrorKind::NotFound))?;
+
+        STANDARD
+            .decode(encoded)
+            .map_err(|e| SimIoError::Corrupt(e.to_string()))
+    }
+
+    pub fn write(path: &Path, bytes: &[u8]) -> Result<(), SimIoError> {
+        storage()?
+            .set_item(&key(path), &STANDARD.encode(bytes))
+            .map_err(js_error)
+    }
+
+    // Browsers only offer asynchronous pickers, so saving and loading go
+    // through a fixed path in localStorage; `download` and `upload` move
+    // files in and out of the browser
+    pub fn pick_save_path() -> Option<PathBuf> {
+        Some(SAVE_PATH.into())
+    }
+
+    pub fn pick_load_path() -> Option<PathBuf> {
+        Some(SAVE_PATH.into())
+    }
+
+    pub fn pick_scenario_save_path() -> Option<PathBuf> {
+        Some(SCENARIO_PATH.into())
+    }
+
+    pub fn pick_scenario_load_path() -> Option<PathBuf> {
+        Some(SCENARIO_PATH.into())
+    }
+
+    /// Hands `bytes` to the browser as a file download called `name`.
+    pub fn download(name: &str, bytes: &[u8]) -> Result<(), SimIoError> {
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or_else(|| Error::new(ErrorKind::Unsupported, "no document"))?;
+
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+        let blob = Blob::new_with_u8_array_sequence(&parts).map_err(js_error)?;
+        let url = Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+
+        let anchor: HtmlAnchorElement = document
+            .create_element("a")
+            .map_err(js_error)?
+            .unchecked_into();
+        anchor.set_href(&url);
+        anchor.set_download(name);
+        anchor.click();
+
+        Url::revoke_object_url(&url).map_err(js_error)
+    }
+
+    /// Asks the user for a file to upload. Its contents show up in the
+    /// returned `Upload` once they've picked one and the browser has read it.
+    pub fn upload() -> Result<Upload, SimIoError> {
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or_else(|| Error::new(ErrorKind::Unsupported, "no document"))?;
+
+        let input: HtmlInputElement = document
+            .create_element("input")
+            .map_err(js_error)?
+            .unchecked_into();
+        input.set_type("file");
+        input.set_accept(".alfs,.json");
+
+        let upload = Upload::default();
+        let result = upload.clone();
+        let picker = input.clone();
+        let on_change = Closure::<dyn FnMut()>::new(move || {
+            let Some(file) = picker.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            let Ok(reader) = FileReader::new() else {
+                return;
+            };
+
+            let result = result.clone();
+            let loaded = reader.clone();
+            let on_load = Closure::<dyn FnMut()>::new(move || {
+                if let Ok(buffer) = loaded.result() {
+                    *result.borrow_mut() = Some(js_sys::Uint8Array::new(&buffer).to_vec());
+                }
+            });
+            reader.set_onload(Some(on_load.as_ref().unchecked_ref()));
+            on_load.forget();
+
+            let _ = reader.read_as_array_buffer(&file);
+        });
+        input.set_onchange(Some(on_change.as_ref().unchecked_ref()));
+        on_change.forget();
+
+        input.click();
+        Ok(upload)
+    }
+}
+
+/// Save files most recently saved or loaded, newest first.
+pub struct RecentFiles {
+    paths: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    pub fn load() -> Self {
+        let paths = read(RECENT_FILES_PATH.as_ref())
+            .map(|recent| {
+                String::from_utf8_lossy(&recent)
+                    .lines()
+                    .map(PathBuf::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self { paths }
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    pub fn add(&mut self, path: &Path) {
+        self.paths.retain(|recent| recent != path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(MAX_RECENT_FILES);
+
+        // Losing the list isn't worth bothering the user about
+        let recent: Vec<_> = self.paths.iter().map(|p| p.display().to_string()).collect();
+        let _ = write(RECENT_FILES_PATH.as_ref(), recent.join("\n").as_bytes());
+    }
+}
@@ -0,0 +1,91 @@
+//! Gamepad controls for the camera and time, for driving the fight from
+//! the couch. Built with the `gamepad` feature.
+//!
+//! The left stick pans, the triggers zoom, south (A on Xbox pads) pauses,
+//! the bumpers step back and forward, the D-pad's up and down double and
+//! halve the speed, and select fits the world in view.
+
+use crate::camera::window_size;
+use crate::{State, SPEED_STEP};
+use gilrs::{Axis, Button, EventType, Gilrs};
+use notan::log;
+use notan::prelude::*;
+
+// Stick and trigger movement below this is noise
+const DEAD_ZONE: f32 = 0.2;
+// Mouse wheel notches per second a fully pulled trigger zooms by
+const ZOOM_RATE: f32 = 6.0;
+
+pub struct Gamepads {
+    // Missing when the platform has no gamepad support
+    gilrs: Option<Gilrs>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        let gilrs = Gilrs::new()
+            .map_err(|err| log::warn!("Gamepads are unavailable: {err}"))
+            .ok();
+        Self { gilrs }
+    }
+}
+
+pub fn control(app: &mut App, state: &mut State) {
+    let Some(gilrs) = &mut state.gamepads.gilrs else {
+        return;
+    };
+
+    let mut pressed = vec![];
+    while let Some(event) = gilrs.next_event() {
+        if let EventType::ButtonPressed(button, _) = event.event {
+            pressed.push(button);
+        }
+    }
+
+    // Every connected pad steers; their sticks add up
+    let (mut pan, mut zoom) = ((0.0, 0.0), 0.0);
+    for (_, pad) in gilrs.gamepads() {
+        pan.0 += dead_zone(pad.value(Axis::LeftStickX));
+        // Up on the stick is positive, up in the window negative
+        pan.1 -= dead_zone(pad.value(Axis::LeftStickY));
+
+        let trigger = |button| {
+            pad.button_data(button)
+                .map_or(0.0, |data| dead_zone(data.value()))
+        };
+        zoom += trigger(Button::RightTrigger2) - trigger(Button::LeftTrigger2);
+    }
+
+    for button in pressed {
+        match button {
+            Button::South => state.set_paused(!state.paused),
+            Button::RightTrigger => state.step_forward(),
+            Button::LeftTrigger => state.step_back(),
+            Button::DPadUp => state.set_speed(state.speed * SPEED_STEP),
+            Button::DPadDown => state.set_speed(state.speed / SPEED_STEP),
+            Button::Select => state.camera.fit_view(&state.world),
+            _ => {}
+        }
+    }
+
+    let size = window_size(app.window().size());
+    let dt = app.timer.delta_f32();
+    if pan != (0.0, 0.0) {
+        let pan = (pan.0.clamp(-1.0, 1.0), pan.1.clamp(-1.0, 1.0));
+        state.camera.steer(pan, dt, size, &state.world);
+    }
+    if zoom != 0.0 {
+        let middle = (size.0 / 2.0, size.1 / 2.0);
+        state
+            .camera
+            .zoom_at(zoom * ZOOM_RATE * dt, middle, size, &state.world);
+    }
+}
+
+fn dead_zone(value: f32) -> f32 {
+    if value.abs() < DEAD_ZONE {
+        0.0
+    } else {
+        value
+    }
+}
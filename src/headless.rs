@@ -0,0 +1,144 @@
+use crate::metrics::Metrics;
+use crate::{Args, Config};
+use alf_core::{Deaths, SaveFormat, SpeciesId, World};
+use serde::Serialize;
+use std::fs::File;
+use std::io::BufWriter;
+
+#[derive(Serialize)]
+struct Summary {
+    seed: u64,
+    ticks: u64,
+    initial_population: usize,
+    final_population: usize,
+    min_population: usize,
+    peak_population: usize,
+    /// Final live cell count per species, keyed by species name
+    species_populations: Vec<(String, usize)>,
+    /// Enemy cells killed per species, keyed by species name
+    species_kills: Vec<(String, u64)>,
+    /// Deaths per species by cause, keyed by species name
+    species_deaths: Vec<(String, Deaths)>,
+    winner: Option<String>,
+    /// How the match ended, if the rules ended it before the last tick
+    match_end: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Output<'a> {
+    summary: Summary,
+    world: &'a World,
+    // Loaded as part of the save, to check it resumes the same way
+    resume_hashes: Vec<u64>,
+}
+
+/// Steps a freshly populated world, or the one saved at `args.load` after
+/// checking it resumes as saved, `args.ticks` times, or until the match
+/// rules end it, without opening a window, then writes the final world and
+/// summary stats to `args.out`. Unless disabled, per-tick metrics are
+/// streamed to `args.metrics` as the run goes.
+pub fn run(args: &Args, seed: u64, config: &Config) -> Result<(), String> {
+    let mut world = match &args.load {
+        Some(path) => std::fs::read(path)
+            .map_err(alf_core::SimIoError::from)
+            .and_then(|bytes| alf_core::load_world(&bytes))
+            .map(|save| {
+                if config.verify_loads {
+                    println!("{}", crate::ui::describe_resume(save.check_resume()));
+                }
+                save.world
+            })
+            .map_err(|e| format!("Cannot load {}: {e}", path.display()))?,
+        None => config.new_world(seed),
+    };
+
+    let initial_population = world.population();
+    let mut min_population = initial_population;
+    let mut peak_population = initial_population;
+    let metrics_error = |e: std::io::Error| format!("Cannot write {}: {e}", args.metrics.display());
+    let mut metrics = if args.no_metrics {
+        None
+    } else {
+        let out = File::create(&args.metrics).map_err(metrics_error)?;
+        Some(Metrics::new(&world, BufWriter::new(out)).map_err(metrics_error)?)
+    };
+
+    let mut match_end = None;
+    for _ in 0..args.ticks {
+        world.step();
+        if let Some(metrics) = &mut metrics {
+            metrics.record(&world).map_err(metrics_error)?;
+        }
+
+        let population = world.population();
+        min_population = min_population.min(population);
+        peak_population = peak_population.max(population);
+
+        if let Some(outcome) = config.rules.evaluate(&world) {
+            match_end = Some(crate::ui::describe_outcome(outcome, world.species()));
+            break;
+        }
+    }
+
+    let summary = Summary {
+        seed: world.seed(),
+        ticks: world.tick(),
+        initial_population,
+        final_population: world.population(),
+        min_population,
+        peak_population,
+        species_populations: world
+            .species()
+            .iter()
+            .map(|species| species.name.clone())
+            .zip(world.populations())
+            .collect(),
+        species_kills: world
+            .species()
+            .iter()
+            .enumerate()
+            .map(|(id, species)| (species.name.clone(), world.kills(id as SpeciesId)))
+            .collect(),
+        species_deaths: world
+            .species()
+            .iter()
+            .enumerate()
+            .map(|(id, species)| (species.name.clone(), world.deaths(id as SpeciesId)))
+            .collect(),
+        winner: world
+            .winner()
+            .map(|id| world.species()[id as usize].name.clone()),
+        match_end,
+    };
+
+    println!(
+        "seed {} ran {} ticks: population {} -> {} (min {}, peak {})",
+        summary.seed,
+        summary.ticks,
+        summary.initial_population,
+        summary.final_population,
+        summary.min_population,
+        summary.peak_population
+    );
+    if let Some(match_end) = &summary.match_end {
+        println!("{match_end}");
+    }
+
+    if let Some(metrics) = metrics {
+        metrics.finish().map_err(metrics_error)?;
+    }
+
+    let bytes = match SaveFormat::from_path(&args.out) {
+        SaveFormat::Json => {
+            let output = Output {
+                summary,
+                world: &world,
+                resume_hashes: world.future_hashes(alf_core::RESUME_TICKS),
+            };
+            serde_json::to_vec(&output).map_err(|e| e.to_string())?
+        }
+        format => alf_core::save_world(&world, None, format).map_err(|e| e.to_string())?,
+    };
+    std::fs::write(&args.out, bytes)
+        .map_err(|e| format!("Cannot write {}: {e}", args.out.display()))
+}
@@ -0,0 +1,78 @@
+//! Overlay of how densely populated each part of the world is.
+
+use crate::coloring::gradient;
+use alf_core::World;
+use notan::prelude::Color;
+
+/// Share of live cells in each square block of the world.
+#[derive(Default)]
+pub struct Heatmap {
+    block: usize,
+    columns: usize,
+    densities: Vec<f32>,
+    // Tick and size of the world the densities are from
+    computed_at: Option<u64>,
+    size: (usize, usize),
+}
+
+impl Heatmap {
+    /// Forgets the densities so the next update recomputes them, for when
+    /// the world changes other than by stepping.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Recomputes the densities in blocks of `block` by `block` cells if
+    /// they're at least `interval` ticks old, the world was rewound past
+    /// them or it changed size.
+    pub fn update(&mut self, world: &World, block: usize, interval: u64) {
+        let fresh = self.computed_at.is_some_and(|tick| {
+            (tick..tick + interval).contains(&world.tick())
+                && self.block == block
+                && self.size == (world.width(), world.height())
+        });
+        if fresh {
+            return;
+        }
+
+        let block = block.max(1);
+        let columns = world.width().div_ceil(block);
+        let rows = world.height().div_ceil(block);
+        let mut counts = vec![0usize; columns * rows];
+        let mut sizes = vec![0usize; columns * rows];
+        for y in 0..world.height() {
+            for x in 0..world.width() {
+                let idx = (y / block) * columns + x / block;
+                sizes[idx] += 1;
+                counts[idx] += world.is_alive(x, y) as usize;
+            }
+        }
+
+        self.block = block;
+        self.columns = columns;
+        self.densities = counts
+            .iter()
+            .zip(sizes)
+            .map(|(count, size)| *count as f32 / size.max(1) as f32)
+            .collect();
+        self.computed_at = Some(world.tick());
+        self.size = (world.width(), world.height());
+    }
+
+    /// Each block's position and size in cells with its color.
+    pub fn blocks(&self) -> impl Iterator<Item = ((usize, usize), usize, Color)> + '_ {
+        self.densities.iter().enumerate().map(|(idx, density)| {
+            let position = (
+                (idx % self.columns) * self.block,
+                (idx / self.columns) * self.block,
+            );
+            (position, self.block, color_map(*density))
+        })
+    }
+}
+
+// See-through so the cells still show
+fn color_map(density: f32) -> Color {
+    // Half of the cells alive is about as dense as life gets
+    gradient(density * 2.0).with_alpha(0.45)
+}
@@ -0,0 +1,225 @@
+//! Named actions and the keys or mouse buttons that trigger them, set in
+//! the `[keys]` table of the config.
+
+use notan::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Pause,
+    StepForward,
+    StepBack,
+    SpeedUp,
+    SlowDown,
+    /// Starts a new world, with the same seed while shift is held.
+    Restart,
+    Screenshot,
+    ToggleStats,
+    ToggleEvents,
+    ToggleHeatmap,
+    ToggleParticles,
+    ToggleMinimap,
+    ToggleDeaths,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ZoomIn,
+    ZoomOut,
+    /// Shows the whole world again.
+    FitView,
+    /// Drags the view around while held.
+    DragView,
+    /// Applies the selected tool, and clicks on the minimap.
+    UseTool,
+    /// Applies the selected tool's erasing side.
+    EraseTool,
+}
+
+impl Action {
+    pub const ALL: [Action; 23] = [
+        Action::Pause,
+        Action::StepForward,
+        Action::StepBack,
+        Action::SpeedUp,
+        Action::SlowDown,
+        Action::Restart,
+        Action::Screenshot,
+        Action::ToggleStats,
+        Action::ToggleEvents,
+        Action::ToggleHeatmap,
+        Action::ToggleParticles,
+        Action::ToggleMinimap,
+        Action::ToggleDeaths,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::PanUp,
+        Action::PanDown,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::FitView,
+        Action::DragView,
+        Action::UseTool,
+        Action::EraseTool,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Pause => "Pause",
+            Action::StepForward => "Step forward",
+            Action::StepBack => "Step back",
+            Action::SpeedUp => "Speed up",
+            Action::SlowDown => "Slow down",
+            Action::Restart => "Restart",
+            Action::Screenshot => "Screenshot",
+            Action::ToggleStats => "Stats",
+            Action::ToggleEvents => "Event log",
+            Action::ToggleHeatmap => "Heatmap",
+            Action::ToggleParticles => "Particles",
+            Action::ToggleMinimap => "Minimap",
+            Action::ToggleDeaths => "Death fades",
+            Action::PanLeft => "Pan left",
+            Action::PanRight => "Pan right",
+            Action::PanUp => "Pan up",
+            Action::PanDown => "Pan down",
+            Action::ZoomIn => "Zoom in",
+            Action::ZoomOut => "Zoom out",
+            Action::FitView => "Fit world in view",
+            Action::DragView => "Drag view",
+            Action::UseTool => "Use tool",
+            Action::EraseTool => "Erase with tool",
+        }
+    }
+
+    fn default_input(self) -> Input {
+        match self {
+            Action::Pause => Input::Key(KeyCode::Space),
+            Action::StepForward => Input::Key(KeyCode::Period),
+            Action::StepBack => Input::Key(KeyCode::Comma),
+            Action::SpeedUp => Input::Key(KeyCode::RBracket),
+            Action::SlowDown => Input::Key(KeyCode::LBracket),
+            Action::Restart => Input::Key(KeyCode::R),
+            Action::Screenshot => Input::Key(KeyCode::F12),
+            Action::ToggleStats => Input::Key(KeyCode::S),
+            Action::ToggleEvents => Input::Key(KeyCode::L),
+            Action::ToggleHeatmap => Input::Key(KeyCode::H),
+            Action::ToggleParticles => Input::Key(KeyCode::P),
+            Action::ToggleMinimap => Input::Key(KeyCode::M),
+            Action::ToggleDeaths => Input::Key(KeyCode::D),
+            Action::PanLeft => Input::Key(KeyCode::Left),
+            Action::PanRight => Input::Key(KeyCode::Right),
+            Action::PanUp => Input::Key(KeyCode::Up),
+            Action::PanDown => Input::Key(KeyCode::Down),
+            Action::ZoomIn => Input::Key(KeyCode::Equals),
+            Action::ZoomOut => Input::Key(KeyCode::Minus),
+            Action::FitView => Input::Key(KeyCode::Home),
+            Action::DragView => Input::Mouse(MouseButton::Middle),
+            Action::UseTool => Input::Mouse(MouseButton::Left),
+            Action::EraseTool => Input::Mouse(MouseButton::Right),
+        }
+    }
+}
+
+/// A key, written `{ Key = "Space" }`, or a mouse button, written
+/// `{ Mouse = "Left" }`, with names as notan has them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Input {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Input {
+    fn was_pressed(self, app: &App) -> bool {
+        match self {
+            Input::Key(key) => app.keyboard.was_pressed(key),
+            Input::Mouse(button) => app.mouse.was_pressed(button),
+        }
+    }
+
+    fn is_down(self, app: &App) -> bool {
+        match self {
+            Input::Key(key) => app.keyboard.is_down(key),
+            Input::Mouse(button) => app.mouse.is_down(button),
+        }
+    }
+
+    fn was_released(self, app: &App) -> bool {
+        match self {
+            Input::Key(key) => app.keyboard.was_released(key),
+            Input::Mouse(button) => app.mouse.was_released(button),
+        }
+    }
+
+    /// The key or button pressed this frame, if any. Escape is left out
+    /// since it cancels rebinding.
+    pub fn pressed(app: &App) -> Option<Input> {
+        let key = app
+            .keyboard
+            .pressed
+            .iter()
+            .find(|key| **key != KeyCode::Escape)
+            .map(|key| Input::Key(*key));
+        key.or_else(|| app.mouse.pressed.iter().next().map(|b| Input::Mouse(*b)))
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Key(key) => {
+                let name = format!("{key:?}");
+                // Number keys are called Key1 and so on
+                write!(f, "{}", name.strip_prefix("Key").unwrap_or(&name))
+            }
+            Input::Mouse(MouseButton::Other(button)) => write!(f, "Mouse {button}"),
+            Input::Mouse(button) => write!(f, "{button:?} mouse"),
+        }
+    }
+}
+
+/// The input of each action. Actions left out of the config keep their
+/// defaults.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bindings(BTreeMap<Action, Input>);
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> Input {
+        self.0
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_input())
+    }
+
+    pub fn set(&mut self, action: Action, input: Input) {
+        self.0.insert(action, input);
+    }
+
+    /// Other actions bound to the same input as `action`.
+    pub fn clashes(&self, action: Action) -> impl Iterator<Item = Action> + '_ {
+        let input = self.get(action);
+        Action::ALL
+            .into_iter()
+            .filter(move |other| *other != action && self.get(*other) == input)
+    }
+
+    /// Puts every action back on its default input.
+    pub fn reset(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn was_pressed(&self, app: &App, action: Action) -> bool {
+        self.get(action).was_pressed(app)
+    }
+
+    pub fn is_down(&self, app: &App, action: Action) -> bool {
+        self.get(action).is_down(app)
+    }
+
+    pub fn was_released(&self, app: &App, action: Action) -> bool {
+        self.get(action).was_released(app)
+    }
+}
@@ -1,196 +1,1429 @@
+mod autosave;
+mod camera;
+mod coloring;
+mod config;
+mod events;
+mod files;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod headless;
+mod heatmap;
+mod input;
+mod metrics;
+mod minimap;
+mod particles;
+mod scenarios;
+mod screenshot;
+mod stats;
+mod tools;
+#[cfg(target_arch = "wasm32")]
+mod touch;
+mod ui;
+
+use alf_core::{
+    Edit, HistoryMode, MatchEnd, Obstacle, Replay, ResumeCheck, SaveFile, SaveFormat, SpeciesId,
+    Timeline, World,
+};
+use autosave::Autosave;
+use camera::{window_size, Camera};
+use clap::Parser;
+use coloring::{species_color, ColorBy, Palette};
+use config::Config;
+use events::{EventKind, EventLog};
+use files::RecentFiles;
+use heatmap::Heatmap;
+use input::{Action, Input};
+use metrics::{Metrics, Recording, METRICS_PATH};
 use notan::draw::*;
+use notan::egui::{EguiConfig, EguiPluginSugar};
+use notan::log;
+use notan::math::{vec2, Mat3};
 use notan::prelude::*;
-use std::convert::TryInto;
+use notan::utils::{Duration, Instant};
+use particles::Particles;
+use stats::Stats;
+use std::path::{Path, PathBuf};
+use tools::{Distribution, Tool};
+
+/// Artificial life fight: Conway's Game of Life in a window, or headless
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Run without a window and write the final state to `--out`
+    #[arg(long)]
+    headless: bool,
+
+    /// Number of generations to simulate in headless mode, unless the match
+    /// rules end it sooner
+    #[arg(long, default_value_t = 1000)]
+    ticks: u64,
+
+    /// Seed for the world RNG, picked at random when omitted
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// File the headless run writes its final state to. `.json` files also
+    /// get the run summary; any other extension uses the binary save format
+    #[arg(long, default_value = "state.json")]
+    out: PathBuf,
+
+    /// Play back a `.alfr` replay file instead of starting a new world
+    #[arg(long)]
+    replay: Option<PathBuf>,
+
+    /// Autosave every this many ticks, 0 to disable
+    #[arg(long, default_value_t = 1000)]
+    autosave_ticks: u64,
+
+    /// Also autosave every this many minutes, 0 to only go by ticks
+    #[arg(long, default_value_t = 0)]
+    autosave_minutes: u64,
+
+    /// Number of autosave files to rotate through
+    #[arg(long, default_value_t = 3)]
+    autosave_slots: usize,
+
+    /// Config file with the simulation parameters, written with the
+    /// defaults if missing
+    #[arg(long, default_value = config::CONFIG_PATH)]
+    config: PathBuf,
 
-const WIDTH: usize = 100;
-const HEIGHT: usize = 100;
-const BYTES_LENGTH: usize = WIDTH * HEIGHT * 4;
+    /// CSV file a headless run writes per-tick metrics to
+    #[arg(long, default_value = METRICS_PATH)]
+    metrics: PathBuf,
+
+    /// Don't write metrics in headless mode
+    #[arg(long)]
+    no_metrics: bool,
+
+    /// Start from a saved world instead of a new one
+    #[arg(long)]
+    load: Option<PathBuf>,
+
+    /// Built-in scenario name or scenario file to use instead of the config
+    #[arg(long)]
+    scenario: Option<String>,
+
+    /// Number of clusters new worlds are seeded with, overriding the config
+    #[arg(long, alias = "entities")]
+    clusters: Option<usize>,
+
+    /// Ticks per second, overriding the config
+    #[arg(long, value_parser = parse_speed)]
+    speed: Option<f32>,
+
+    /// Window size as WIDTHxHEIGHT, four pixels per cell when omitted
+    #[arg(long, value_parser = parse_window_size)]
+    window: Option<(u32, u32)>,
+}
+
+fn parse_speed(speed: &str) -> Result<f32, String> {
+    match speed.parse() {
+        Ok(speed) if speed > 0.0 => Ok(speed),
+        _ => Err(format!("expected a positive number, got {speed}")),
+    }
+}
+
+fn parse_window_size(size: &str) -> Result<(u32, u32), String> {
+    let error = || format!("expected WIDTHxHEIGHT, got {size}");
+    let (width, height) = size.split_once('x').ok_or_else(error)?;
+    let width = width.trim().parse().map_err(|_| error())?;
+    let height = height.trim().parse().map_err(|_| error())?;
+    Ok((width, height))
+}
+
+impl Args {
+    /// Applies the flags that override config values. They hold until the
+    /// config is reloaded.
+    fn apply_overrides(&self, config: &mut Config) {
+        if let Some(clusters) = self.clusters {
+            config.initial_clusters = clusters;
+        }
+
+        if let Some(speed) = self.speed {
+            config.ticks_per_second = speed;
+        }
+    }
+}
+
+const REPLAY_PATH: &str = "replay.alfr";
+const SAVE_PATH: &str = "world.alfs";
+const SCENARIO_PATH: &str = "scenario.toml";
+
+const SAFE_ZONE_COLOR: Color = Color::new(1.0, 0.6, 0.0, 0.8);
+
+// How long a toast stays up unless clicked away
+const TOAST_SECONDS: f32 = 5.0;
+
+// Message shown at the bottom of the window for a few seconds
+struct Toast {
+    message: String,
+    error: bool,
+    seconds: f32,
+}
+
+/// A run of ticks stepped as fast as possible.
+#[derive(Clone, Copy)]
+struct Turbo {
+    from: u64,
+    to: u64,
+}
 
 #[derive(AppState)]
 struct State {
+    config: Config,
+    config_path: PathBuf,
     texture: Texture,
-    current_bytes: [u8; BYTES_LENGTH],
-    previous_bytes: [u8; BYTES_LENGTH],
+    camera: Camera,
+    world: World,
+    bytes: Vec<u8>,
     count: f32,
     dirty: bool,
+    // The world as drawn a tick earlier, blended into the current one
+    // between ticks when `smooth` is on
+    smooth: bool,
+    previous_texture: Texture,
+    previous_bytes: Vec<u8>,
+    previous_tick: Option<u64>,
+    previous_dirty: bool,
+    death_fades: Vec<(u8, SpeciesId)>,
+    show_deaths: bool,
+    color_by: ColorBy,
+    // How the match ended, once it's over
+    outcome: Option<MatchEnd>,
+    show_outcome: bool,
+    timeline: Timeline,
+    paused: bool,
+    turbo: Option<Turbo>,
+    // Ticks the next turbo run goes for
+    turbo_ticks: u64,
+    // Multiplies the configured ticks per second
+    speed: f32,
+    // Index of the history snapshot the world was rewound to, if any
+    rewound: Option<usize>,
+    branch_on_resume: bool,
+    recording: Option<Replay>,
+    // Replay being played back, whose edits are applied as its ticks come
+    playback: Option<Replay>,
+    toast: Option<Toast>,
+    autosave: Autosave,
+    // Autosave offered for restoring after the previous session crashed
+    recovery: Option<PathBuf>,
+    recent_files: RecentFiles,
+    #[cfg(target_arch = "wasm32")]
+    upload: Option<files::Upload>,
+    stats: Stats,
+    heatmap: Heatmap,
+    show_heatmap: bool,
+    particles: Particles,
+    show_particles: bool,
+    // Tick range and ticks per frame of the GIF being set up
+    clip: Option<(u64, u64, u64)>,
+    gif_export: Option<screenshot::GifExport>,
+    events: EventLog,
+    show_events: bool,
+    // Kinds of event shown in the event log
+    event_filter: Vec<EventKind>,
+    metrics: Option<Metrics<Recording>>,
+    show_stats: bool,
+    tool: Tool,
+    // Cell shown in the inspector
+    selected: Option<(usize, usize)>,
+    spawn_species: SpeciesId,
+    // Radius in cells of the disc the tools paint
+    brush_radius: f32,
+    scatter_count: usize,
+    distribution: Distribution,
+    hazard_damage: u8,
+    // Whether a tool is being dragged over the world
+    editing: bool,
+    // Corners of the box being dragged out with the erase box tool
+    erase_box: Option<((isize, isize), (isize, isize))>,
+    // Whether egui had the pointer last frame, so clicks aren't also edits
+    pointer_over_ui: bool,
+    show_minimap: bool,
+    show_keys: bool,
+    // Action waiting for the next key or click to be bound to it
+    rebinding: Option<Action>,
+    #[cfg(feature = "gamepad")]
+    gamepads: gamepad::Gamepads,
+    #[cfg(target_arch = "wasm32")]
+    touches: touch::Touches,
 }
 
 impl State {
-    fn is_alive(&self, x: usize, y: usize) -> bool {
-        let neighbors = get_neighbors(x as _, y as _);
-        let count = neighbors.iter().fold(0, |sum, (x, y)| {
-            let idx = index(*x, *y);
-            match idx {
-                Some(idx) => {
-                    let is_red =
-                        is_red_color(&self.previous_bytes[idx..idx + 4].try_into().unwrap());
-                    if is_red {
-                        sum + 1
-                    } else {
-                        sum
-                    }
+    /// Makes `world` the live world with a fresh timeline.
+    fn start(&mut self, world: World) {
+        self.stop_recording();
+        self.stop_metrics();
+        self.turbo = None;
+
+        // The view stays where it was unless the world's size changed
+        if (world.width(), world.height()) != (self.world.width(), self.world.height()) {
+            self.camera = Camera::fit(&world);
+        }
+        self.world = world;
+        self.clear_death_fades();
+        self.particles.clear();
+        self.heatmap.clear();
+        self.outcome = None;
+        self.show_outcome = false;
+        self.count = 0.0;
+        self.rewound = None;
+        self.playback = None;
+        self.timeline.clear();
+        self.timeline.push(&self.world);
+        self.stats.clear();
+        self.stats.record(&self.world);
+        self.events.reset(&self.world);
+        self.refresh_bytes();
+    }
+
+    /// Steps the live world one tick and redraws it.
+    fn advance(&mut self) {
+        if self.smooth {
+            self.previous_bytes.clone_from(&self.bytes);
+            self.previous_tick = Some(self.world.tick());
+            self.previous_dirty = true;
+        }
+
+        self.simulate_tick();
+        self.refresh_bytes();
+        if self.show_particles {
+            self.particles
+                .emit(&self.world, self.config.particle_budget);
+        }
+    }
+
+    /// Steps the live world one tick, recording it in the history, the stats
+    /// and the replay being recorded, but without redrawing it.
+    fn simulate_tick(&mut self) {
+        self.world.step();
+        let edited = self.play_edits();
+        self.advance_death_fades();
+        self.stats.record(&self.world);
+        self.events.observe(&self.world);
+        if let Some(metrics) = &mut self.metrics {
+            if let Err(err) = metrics.record(&self.world) {
+                self.metrics = None;
+                self.report_error(format!("Cannot save metrics to {METRICS_PATH}: {err}"));
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        if self.metrics.as_ref().is_some_and(Metrics::is_full) {
+            self.stop_metrics();
+        }
+
+        if edited
+            || self
+                .world
+                .tick()
+                .is_multiple_of(self.config.snapshot_interval)
+        {
+            // Edits can't be reached by stepping, so re-simulated snapshots
+            // taken during a drag are stored in full until it ends, and so
+            // are replayed edits
+            let resimulated = self.timeline.history().mode() == HistoryMode::Resimulate;
+            if edited || (self.editing && resimulated) {
+                self.timeline.push_keyframe(&self.world);
+            } else {
+                self.timeline.push(&self.world);
+            }
+        }
+
+        if let Some(replay) = &mut self.recording {
+            replay.record_tick();
+        }
+
+        if self.autosave.is_due(self.world.tick()) {
+            match self.autosave.save(&self.world, &self.timeline) {
+                Ok(path) => {
+                    log::info!("Autosaved tick {} to {}", self.world.tick(), path.display())
+                }
+                Err(err) => self.report_error(format!("Cannot autosave: {err}")),
+            }
+        }
+
+        let tick = self.world.tick();
+        if self
+            .playback
+            .as_ref()
+            .is_some_and(|replay| replay.end_tick() == tick)
+        {
+            log::info!("Replay finished at tick {tick}");
+            self.playback = None;
+            self.paused = true;
+            self.turbo = None;
+        }
+
+        self.check_outcome();
+    }
+
+    // Ends the match once the rules say it's over
+    fn check_outcome(&mut self) {
+        if self.outcome.is_some() {
+            return;
+        }
+
+        self.outcome = self.config.rules.evaluate(&self.world);
+        if let Some(outcome) = self.outcome {
+            let description = ui::describe_outcome(outcome, self.world.species());
+            log::info!("{description} after {} ticks", self.world.tick());
+            self.events
+                .push(self.world.tick(), EventKind::MatchEnd, description);
+            self.set_paused(true);
+            self.turbo = None;
+            self.show_outcome = true;
+        }
+    }
+
+    /// Applies `edit` to the live world, recording it in the replay being
+    /// recorded.
+    fn edit(&mut self, edit: Edit) {
+        edit.apply(&mut self.world);
+        if let Some(replay) = &mut self.recording {
+            replay.record_edit(self.world.tick(), edit);
+        }
+    }
+
+    // Applies the edits the replay being played back made at this tick.
+    // Returns whether there were any
+    fn play_edits(&mut self) -> bool {
+        let Some(replay) = &self.playback else {
+            return false;
+        };
+
+        let mut edited = false;
+        for edit in replay.edits_at(self.world.tick()) {
+            edit.apply(&mut self.world);
+            edited = true;
+        }
+        edited
+    }
+
+    fn start_recording(&mut self) {
+        self.recording = Some(Replay::new(&self.world));
+    }
+
+    fn stop_recording(&mut self) {
+        let Some(replay) = self.recording.take() else {
+            return;
+        };
+
+        let saved = replay
+            .to_bytes()
+            .and_then(|bytes| files::write(REPLAY_PATH.as_ref(), &bytes));
+        match saved {
+            Ok(()) => log::info!("Saved {} ticks of replay to {REPLAY_PATH}", replay.ticks),
+            Err(err) => self.report_error(format!("Cannot save replay to {REPLAY_PATH}: {err}")),
+        }
+    }
+
+    fn start_metrics(&mut self) {
+        match Metrics::start(&self.world) {
+            Ok(metrics) => self.metrics = Some(metrics),
+            Err(err) => self.report_error(format!("Cannot save metrics to {METRICS_PATH}: {err}")),
+        }
+    }
+
+    /// Finishes writing the metrics recorded so far to `METRICS_PATH`.
+    fn stop_metrics(&mut self) {
+        let Some(metrics) = self.metrics.take() else {
+            return;
+        };
+
+        let rows = metrics.rows();
+        match metrics.save() {
+            Ok(()) => log::info!("Saved {rows} ticks of metrics to {METRICS_PATH}"),
+            Err(err) => self.report_error(format!("Cannot save metrics to {METRICS_PATH}: {err}")),
+        }
+    }
+
+    /// Saves the world with its timeline, every branch included.
+    fn save(&mut self, path: &Path) {
+        let timeline = Some(&self.timeline);
+        let saved = alf_core::save_world(&self.world, timeline, SaveFormat::from_path(path))
+            .and_then(|bytes| files::write(path, &bytes));
+        match saved {
+            Ok(()) => {
+                log::info!("Saved tick {} to {}", self.world.tick(), path.display());
+                self.recent_files.add(path);
+            }
+            Err(err) => self.report_error(format!("Cannot save to {}: {err}", path.display())),
+        }
+    }
+
+    /// Starts a new world from `config`, which replaces the current one
+    /// until the config is reloaded. Key bindings and display settings are
+    /// the user's, so they're kept.
+    fn start_scenario(&mut self, mut config: Config) {
+        config.keys = std::mem::take(&mut self.config.keys);
+        config.camera_clamp = self.config.camera_clamp;
+        config.particle_budget = self.config.particle_budget;
+        config.verify_loads = self.config.verify_loads;
+        self.config = config;
+        reset(self, Random::default().gen());
+    }
+
+    fn load_scenario(&mut self, path: &Path) {
+        match scenarios::load(&path.display().to_string()) {
+            Ok(config) => {
+                log::info!("Loaded scenario {}", path.display());
+                self.start_scenario(config);
+            }
+            Err(err) => self.report_error(err),
+        }
+    }
+
+    /// Saves the world's current layout as a scenario new worlds start from.
+    fn save_scenario(&mut self, path: &Path) {
+        // Pretty printing would put every coordinate of the layout on a line
+        // of its own
+        let saved = toml::to_string(&self.config.capture(&self.world))
+            .map_err(|e| e.to_string())
+            .and_then(|text| files::write(path, text.as_bytes()).map_err(|e| e.to_string()));
+        match saved {
+            Ok(()) => log::info!("Saved scenario to {}", path.display()),
+            Err(err) => self.report_error(format!("Cannot save to {}: {err}", path.display())),
+        }
+    }
+
+    /// Kills every cell and pauses, leaving walls and hazards, so a
+    /// scenario can be laid out with the tools.
+    fn clear_world(&mut self) {
+        self.set_paused(true);
+        for y in 0..self.world.height() {
+            for x in 0..self.world.width() {
+                if self.world.is_alive(x, y) {
+                    self.edit(Edit::Kill {
+                        x: x as _,
+                        y: y as _,
+                    });
+                }
+            }
+        }
+        self.refresh_bytes();
+        self.finish_edit();
+    }
+
+    fn load(&mut self, path: &Path) {
+        let world = files::read(path).and_then(|bytes| alf_core::load_world(&bytes));
+        match world {
+            Ok(save) => {
+                log::info!("Loaded tick {} from {}", save.world.tick(), path.display());
+                self.recent_files.add(path);
+                self.check_resume(&save);
+                self.start_save(save);
+            }
+            Err(err) => self.report_error(format!("Cannot load {}: {err}", path.display())),
+        }
+    }
+
+    /// Makes a loaded world the live one, with the timeline it was saved
+    /// with if any.
+    fn start_save(&mut self, save: SaveFile) {
+        self.start(save.world);
+        let Some(timeline) = save.timeline else {
+            return;
+        };
+
+        self.timeline.restore(timeline);
+        // Saved while rewound, so resuming leaves the past as it would have
+        let history = self.timeline.history();
+        if history
+            .latest()
+            .is_some_and(|latest| latest.tick() != self.world.tick())
+        {
+            self.rewound = history.index_at_or_before(self.world.tick());
+            self.paused = true;
+        }
+    }
+
+    fn play_replay(&mut self, path: &Path) {
+        let replay = files::read(path).and_then(|bytes| Replay::from_bytes(&bytes));
+
+        match replay {
+            Ok(replay) => {
+                log::info!("Playing {} ticks from {}", replay.ticks, path.display());
+                self.start(replay.start.clone());
+                self.playback = Some(replay);
+                if self.play_edits() {
+                    self.timeline.push_keyframe(&self.world);
                 }
-                _ => sum,
+                self.paused = false;
             }
+            Err(err) => self.report_error(format!("Cannot play {}: {err}", path.display())),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn download(&mut self) {
+        let timeline = Some(&self.timeline);
+        let downloaded = alf_core::save_world(&self.world, timeline, SaveFormat::Binary)
+            .and_then(|bytes| files::download(SAVE_PATH, &bytes));
+        if let Err(err) = downloaded {
+            self.report_error(format!("Cannot download the world: {err}"));
+        }
+    }
+
+    /// Saves a PNG of the world as it's drawn, without overlays, on the web
+    /// as a download.
+    fn screenshot(&mut self) {
+        let png = screenshot::encode(
+            &self.bytes,
+            self.world.width(),
+            self.world.height(),
+            self.config.screenshot_area,
+            self.config.screenshot_scale,
+        );
+        self.export(&screenshot::file_name("screenshot", "png"), png);
+    }
+
+    /// Starts exporting a GIF of the ticks from `start` to `end` with a
+    /// frame every `every` ticks, played back at the configured speed.
+    fn export_clip(&mut self, start: u64, end: u64, every: u64) {
+        let frame_ms = (every as f32 * self.config.step_size() * 1000.0) as u32;
+        let clip = screenshot::Clip {
+            start,
+            end,
+            every,
+            // Viewers slow down frames shorter than this
+            frame_ms: frame_ms.max(20),
+            color_by: self.color_by,
+            area: self.config.screenshot_area,
+            scale: self.config.screenshot_scale,
+        };
+        match screenshot::GifExport::new(self.timeline.history(), clip) {
+            Ok(export) => self.gif_export = Some(export),
+            Err(err) => self.report_error(format!("Cannot export the clip: {err}")),
+        }
+    }
+
+    // Encodes the next few frames of the GIF being exported, saving it once
+    // it's done
+    fn run_gif_export(&mut self) {
+        let Some(export) = &mut self.gif_export else {
+            return;
+        };
+
+        let gif = match export.encode(EXPORT_FRAME_TIME) {
+            Ok(None) => return,
+            Ok(Some(gif)) => Ok(gif),
+            Err(err) => Err(err),
+        };
+        self.gif_export = None;
+        self.export(&screenshot::file_name("clip", "gif"), gif);
+    }
+
+    // Writes an export next to the saves, on the web as a download
+    fn export(&mut self, name: &str, bytes: Result<Vec<u8>, String>) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let saved =
+            bytes.and_then(|bytes| files::write(name.as_ref(), &bytes).map_err(|e| e.to_string()));
+        #[cfg(target_arch = "wasm32")]
+        let saved =
+            bytes.and_then(|bytes| files::download(name, &bytes).map_err(|e| e.to_string()));
+
+        match saved {
+            Ok(()) => log::info!("Saved {name}"),
+            Err(err) => self.report_error(format!("Cannot save {name}: {err}")),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn upload(&mut self) {
+        match files::upload() {
+            Ok(upload) => self.upload = Some(upload),
+            Err(err) => self.report_error(format!("Cannot upload a world: {err}")),
+        }
+    }
+
+    // Loads the uploaded world once the browser has finished reading it
+    #[cfg(target_arch = "wasm32")]
+    fn poll_upload(&mut self) {
+        let Some(bytes) = self.upload.as_ref().and_then(|upload| upload.take()) else {
+            return;
+        };
+
+        self.upload = None;
+        match alf_core::load_world(&bytes) {
+            Ok(save) => {
+                log::info!("Loaded tick {} from the uploaded file", save.world.tick());
+                self.check_resume(&save);
+                self.start_save(save);
+            }
+            Err(err) => self.report_error(format!("Cannot load the uploaded file: {err}")),
+        }
+    }
+
+    // Failed file operations are logged and shown in a toast; the session
+    // carries on with the current world
+    fn report_error(&mut self, message: String) {
+        log::error!("{message}");
+        self.toast = Some(Toast {
+            message,
+            error: true,
+            seconds: TOAST_SECONDS,
+        });
+    }
+
+    fn report(&mut self, message: String) {
+        log::info!("{message}");
+        self.toast = Some(Toast {
+            message,
+            error: false,
+            seconds: TOAST_SECONDS,
+        });
+    }
+
+    // Steps a copy of a loaded world to check it carries on the way the
+    // saved run would have
+    fn check_resume(&mut self, save: &SaveFile) {
+        if !self.config.verify_loads {
+            return;
+        }
+        let check = save.check_resume();
+        match check {
+            ResumeCheck::Unchecked => {}
+            ResumeCheck::Verified => self.report(ui::describe_resume(check)),
+            ResumeCheck::Diverged(_) => self.report_error(ui::describe_resume(check)),
+        }
+    }
+
+    /// Runs `ticks` ticks of the live world as fast as possible, a frame's
+    /// worth of time at a time, pausing it when done.
+    fn start_turbo(&mut self, ticks: u64) {
+        self.leave_past();
+        self.paused = true;
+        self.turbo = Some(Turbo {
+            from: self.world.tick(),
+            to: self.world.tick() + ticks,
         });
+    }
 
-        let was_alive = match index(x as _, y as _) {
-            Some(idx) => is_red_color(&self.previous_bytes[idx..idx + 4].try_into().unwrap()),
-            _ => false,
+    // Steps the world until the turbo run's done or the frame's time is up,
+    // then redraws it once
+    fn run_turbo(&mut self, turbo: Turbo) {
+        let started = Instant::now();
+        while self.world.tick() < turbo.to
+            && self.turbo.is_some()
+            && started.elapsed() < TURBO_FRAME_TIME
+        {
+            self.simulate_tick();
+        }
+        self.refresh_bytes();
+
+        if self.world.tick() >= turbo.to {
+            log::info!("Turbo run reached tick {}", self.world.tick());
+            self.turbo = None;
+        }
+    }
+
+    /// Seconds between ticks at the current speed.
+    fn step_size(&self) -> f32 {
+        self.config.step_size() / self.speed
+    }
+
+    fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        // Playing normally ends a turbo run
+        if !paused {
+            self.leave_past();
+            self.turbo = None;
+        }
+
+        self.paused = paused;
+    }
+
+    // Resuming or editing at a rewound point either forks a new branch there
+    // or overwrites the old future
+    fn leave_past(&mut self) {
+        if let Some(index) = self.rewound.take() {
+            // Metrics cover one unbroken run
+            self.stop_metrics();
+            if self.branch_on_resume {
+                self.timeline.fork(index);
+            } else {
+                self.timeline.history_mut().truncate(index);
+            }
+        }
+    }
+
+    /// Records an edit of the live world in the history. Edits can't be
+    /// reached by stepping, so they're stored as a keyframe.
+    fn finish_edit(&mut self) {
+        self.leave_past();
+        self.timeline.push_keyframe(&self.world);
+        self.heatmap.clear();
+        self.events.push(
+            self.world.tick(),
+            EventKind::Edit,
+            "Edited the world".to_string(),
+        );
+        // Edited cells aren't deaths or kills
+        self.events.rebase(&self.world);
+    }
+
+    fn rewind_to(&mut self, index: usize) {
+        if let Some(world) = self.timeline.history().get(index) {
+            self.show_world(world);
+            self.rewound = Some(index);
+        }
+    }
+
+    fn step_forward(&mut self) {
+        self.paused = true;
+
+        match self.rewound {
+            // Replaying the past; follow along in the history
+            Some(_) => {
+                self.world.step();
+                self.advance_death_fades();
+                self.refresh_bytes();
+                self.events.rebase(&self.world);
+                self.rewound = self
+                    .timeline
+                    .history()
+                    .index_at_or_before(self.world.tick())
+            }
+            None => self.advance(),
+        }
+    }
+
+    /// Rewinds or fast-forwards through the history to `tick`.
+    fn jump_to(&mut self, tick: u64) {
+        let history = self.timeline.history();
+        if let Some(world) = history.world_at_tick(tick) {
+            self.rewound = history.index_at_or_before(tick);
+            self.show_world(world);
+        }
+    }
+
+    fn step_back(&mut self) {
+        let Some(tick) = self.world.tick().checked_sub(1) else {
+            return;
         };
 
-        if was_alive {
-            count == 2 || count == 3
-        } else {
-            count == 3
+        let history = self.timeline.history();
+        if let Some(world) = history.world_at_tick(tick) {
+            self.rewound = history.index_at_or_before(tick);
+            self.show_world(world);
+        }
+    }
+
+    fn switch_branch(&mut self, branch: usize) {
+        if let Some(world) = self.timeline.switch_to(branch) {
+            self.show_world(world);
+            self.rewound = None;
+        }
+    }
+
+    // A recording only covers a straight run, so it ends when jumping around
+    // the timeline
+    fn show_world(&mut self, world: World) {
+        self.stop_recording();
+        self.turbo = None;
+
+        self.world = world;
+        self.outcome = self.config.rules.evaluate(&self.world);
+        self.show_outcome = false;
+        self.paused = true;
+        self.clear_death_fades();
+        self.particles.clear();
+        self.heatmap.clear();
+        self.refresh_bytes();
+        // The log already has what happened in the past being shown
+        self.events.rebase(&self.world);
+    }
+
+    /// Writes the config, with any rebound keys, back to its file.
+    fn save_config(&mut self) {
+        match self.config.save(&self.config_path) {
+            Ok(()) => log::info!("Saved {}", self.config_path.display()),
+            Err(err) => self.report_error(err),
         }
     }
 
-    fn swap_data(&mut self) {
-        std::mem::swap(&mut self.current_bytes, &mut self.previous_bytes);
+    /// Rereads the config file. Speed, snapshot interval and death fades
+    /// change right away, world settings apply to the next new world.
+    fn reload_config(&mut self) {
+        match Config::load(&self.config_path) {
+            Ok(config) => {
+                log::info!("Reloaded {}", self.config_path.display());
+                self.config = config;
+            }
+            Err(err) => self.report_error(err),
+        }
+    }
+
+    fn clear_death_fades(&mut self) {
+        self.death_fades = vec![(0, 0); self.world.width() * self.world.height()];
+    }
+
+    // Dead cells are drawn in a pale tint of their species that fades back to
+    // white over a few ticks; this is purely visual. Called once per tick,
+    // so redraws while editing or scrubbing don't hurry the fade along
+    fn advance_death_fades(&mut self) {
+        if !self.show_deaths {
+            return;
+        }
+
+        let steps = self.config.death_fade_steps;
+        let width = self.world.width();
+        for y in 0..self.world.height() {
+            for x in 0..width {
+                if self.world.is_alive(x, y) {
+                    continue;
+                }
+
+                let (fade, species) = &mut self.death_fades[y * width + x];
+                if let Some(cell) = self.world.previous_cell(x, y) {
+                    *fade = steps;
+                    *species = cell.species;
+                } else {
+                    *fade = fade.saturating_sub(1);
+                }
+            }
+        }
+    }
+
+    fn dead_color(&self, x: usize, y: usize) -> Color {
+        if !self.show_deaths {
+            return Color::WHITE;
+        }
+
+        let steps = self.config.death_fade_steps;
+        let (fade, species) = self.death_fades[y * self.world.width() + x];
+        if fade == 0 {
+            return Color::WHITE;
+        }
+
+        let t = 1.0 - fade as f32 / (steps as f32 + 1.0);
+        let [r, g, b, _] = species_color(&self.world.species()[species as usize]).rgba();
+        Color::new(r + (1.0 - r) * t, g + (1.0 - g) * t, b + (1.0 - b) * t, 1.0)
+    }
+
+    fn refresh_bytes(&mut self) {
+        // Loaded worlds may differ in size from the last one
+        self.bytes
+            .resize(self.world.width() * self.world.height() * 4, 255);
+
+        let palette = Palette::new(&self.world, self.color_by);
+        for y in 0..self.world.height() {
+            for x in 0..self.world.width() {
+                let color = match palette.color(&self.world, x, y) {
+                    Some(color) => color,
+                    None => self.dead_color(x, y),
+                };
+
+                self.set_color(color, x, y);
+            }
+        }
+
         self.dirty = true;
     }
 
-    fn set_color(&mut self, color: Color, x: usize, y: usize) {
-        if let Some(idx) = index(x as _, y as _) {
-            self.current_bytes[idx..idx + 4].copy_from_slice(&color.rgba_u8());
+    /// How far to fade from the previous tick's image to the current one,
+    /// or `None` to draw the current one alone.
+    fn blend(&self) -> Option<f32> {
+        let follows = self
+            .previous_tick
+            .is_some_and(|tick| tick + 1 == self.world.tick());
+        if !self.smooth || self.paused || !follows || self.previous_bytes.len() != self.bytes.len()
+        {
+            return None;
         }
+
+        Some((self.count / self.step_size()).min(1.0))
+    }
+
+    fn set_color(&mut self, color: Color, x: usize, y: usize) {
+        let idx = ((y * self.world.width()) + x) * 4;
+        self.bytes[idx..idx + 4].copy_from_slice(&color.rgba_u8());
     }
 }
 
 #[notan_main]
 fn main() -> Result<(), String> {
-    let width = WIDTH * 4;
-    let height = HEIGHT * 4;
+    let args = Args::parse();
+    let seed = args.seed.unwrap_or_else(|| Random::default().gen());
+
+    let config = match &args.scenario {
+        Some(scenario) => scenarios::load(scenario),
+        None => Config::load(&args.config),
+    };
+    if args.headless {
+        let mut config = config?;
+        args.apply_overrides(&mut config);
+        return headless::run(&args, seed, &config);
+    }
+
+    // A broken config shouldn't stop the window from opening; the error is
+    // shown once it's up
+    let (mut config, config_error) = match config {
+        Ok(config) => (config, None),
+        Err(err) => (Config::default(), Some(err)),
+    };
+    args.apply_overrides(&mut config);
+
+    let (width, height) = args
+        .window
+        .unwrap_or((config.width as u32 * 4, config.height as u32 * 4));
 
     let win_config = WindowConfig::new().size(width as _, height as _);
 
-    notan::init_with(setup)
-        .initialize(init)
+    // There's no file system to autosave to on the web
+    let (autosave_ticks, autosave_minutes) = if cfg!(target_arch = "wasm32") {
+        (0, 0)
+    } else {
+        (args.autosave_ticks, args.autosave_minutes)
+    };
+    let autosave = Autosave::new(autosave_ticks, autosave_minutes, args.autosave_slots);
+
+    let config_path = args.config.clone();
+    notan::init_with(move |gfx: &mut Graphics| setup(gfx, config, config_path, seed, autosave))
+        .initialize(move |state: &mut State| init(state, args, config_error))
         .add_config(win_config)
         .add_config(DrawConfig)
+        .add_config(EguiConfig)
         .update(update)
+        .event(event)
         .draw(draw)
         .build()
 }
 
-fn setup(gfx: &mut Graphics) -> State {
-    let current_bytes = [255; BYTES_LENGTH];
-    let previous_bytes = current_bytes;
-
-    let texture = gfx
-        .create_texture()
-        .from_bytes(&current_bytes, WIDTH as _, HEIGHT as _)
-        .build()
-        .unwrap();
+fn setup(
+    gfx: &mut Graphics,
+    config: Config,
+    config_path: PathBuf,
+    seed: u64,
+    autosave: Autosave,
+) -> State {
+    let world = World::new(config.width, config.height, seed);
+    let bytes = vec![255; world.width() * world.height() * 4];
+    let texture = create_texture(gfx, &world, &bytes);
+    let previous_texture = create_texture(gfx, &world, &bytes);
+    let timeline = Timeline::new(
+        config.history_max_snapshots,
+        config.history_max_mib * 1024 * 1024,
+        config.history_keyframe_interval,
+    );
 
     State {
+        config,
+        config_path,
         texture,
-        current_bytes,
-        previous_bytes,
+        camera: Camera::fit(&world),
+        death_fades: vec![(0, 0); world.width() * world.height()],
+        world,
+        bytes,
         count: 0.0,
         dirty: false,
+        smooth: false,
+        previous_texture,
+        previous_bytes: vec![],
+        previous_tick: None,
+        previous_dirty: false,
+        show_deaths: true,
+        color_by: ColorBy::Species,
+        outcome: None,
+        show_outcome: false,
+        timeline,
+        paused: false,
+        turbo: None,
+        turbo_ticks: 1000,
+        speed: 1.0,
+        rewound: None,
+        branch_on_resume: false,
+        recording: None,
+        playback: None,
+        toast: None,
+        autosave,
+        recovery: None,
+        recent_files: RecentFiles::load(),
+        #[cfg(target_arch = "wasm32")]
+        upload: None,
+        stats: Stats::default(),
+        heatmap: Heatmap::default(),
+        show_heatmap: false,
+        particles: Particles::default(),
+        show_particles: false,
+        clip: None,
+        gif_export: None,
+        events: EventLog::default(),
+        show_events: false,
+        event_filter: EventKind::ALL.to_vec(),
+        metrics: None,
+        show_stats: false,
+        tool: Tool::None,
+        selected: None,
+        spawn_species: 0,
+        brush_radius: 2.0,
+        scatter_count: 20,
+        distribution: Distribution::Disc,
+        hazard_damage: 1,
+        editing: false,
+        erase_box: None,
+        pointer_over_ui: false,
+        show_minimap: true,
+        show_keys: false,
+        rebinding: None,
+        #[cfg(feature = "gamepad")]
+        gamepads: gamepad::Gamepads::new(),
+        #[cfg(target_arch = "wasm32")]
+        touches: touch::Touches::default(),
     }
 }
 
-fn init(state: &mut State) {
-    let mut rng = Random::default();
-    for _ in 0..500 {
-        let x = rng.gen_range(0..WIDTH);
-        let y = rng.gen_range(0..HEIGHT);
+fn init(state: &mut State, args: Args, config_error: Option<String>) {
+    if let Some(err) = config_error {
+        state.report_error(format!("{err}, using the default config"));
+    }
 
-        let neighbors = get_neighbors(x as _, y as _);
-        neighbors.iter().for_each(|(x, y)| {
-            let valid_coords = index(*x, *y).is_some();
-            if valid_coords {
-                state.set_color(Color::RED, *x as _, *y as _);
-            }
-        });
+    reset(state, state.world.seed());
+
+    // Hold the new world until the user decides, so the autosave being
+    // offered isn't overwritten in the meantime
+    if autosave::begin_session() {
+        state.recovery = state.autosave.latest();
+        state.paused = state.recovery.is_some();
     }
 
-    state.swap_data();
+    if let Some(path) = args.load {
+        state.load(&path);
+    }
+
+    if let Some(path) = args.replay {
+        state.play_replay(&path);
+    }
+}
+
+fn create_texture(gfx: &mut Graphics, world: &World, bytes: &[u8]) -> Texture {
+    gfx.create_texture()
+        .from_bytes(bytes, world.width() as _, world.height() as _)
+        .build()
+        .unwrap()
 }
 
-const STEP_SIZE: f32 = 0.05;
+fn reset(state: &mut State, seed: u64) {
+    log::info!("Starting world with seed {seed}");
+
+    let world = state.config.new_world(seed);
+    state.start(world);
+}
+
+// A slow frame advances the world at most this many ticks, times the speed
+// when sped up; the rest of the backlog is dropped so a stall doesn't turn
+// into a burst of catch-up ticks
+const MAX_TICKS_PER_FRAME: u32 = 5;
+
+// Time per frame a turbo run spends stepping the world, leaving the rest
+// of the frame to keep the window responsive
+const TURBO_FRAME_TIME: Duration = Duration::from_millis(25);
+
+// Time per frame spent encoding a GIF being exported
+const EXPORT_FRAME_TIME: Duration = Duration::from_millis(20);
+
+// Range of the speed multiplier, and the factor the speed keys change it by
+const MIN_SPEED: f32 = 0.1;
+const MAX_SPEED: f32 = 32.0;
+const SPEED_STEP: f32 = 2.0;
 
 #[cfg(target_arch = "wasm32")]
 fn check_for_exit(app: &mut App) {}
 
 #[cfg(not(target_arch = "wasm32"))]
 fn check_for_exit(app: &mut App) {
-    if app.keyboard.is_down(KeyCode::Escape) {
+    if app.keyboard.was_pressed(KeyCode::Escape) {
         app.exit();
     }
 }
 
-fn update(app: &mut App, state: &mut State) {
-    state.count += app.timer.delta_f32();
+fn hotkeys(app: &mut App, state: &mut State) {
+    let pressed: Vec<Action> = Action::ALL
+        .into_iter()
+        .filter(|action| state.config.keys.was_pressed(app, *action))
+        .collect();
 
-    while state.count >= STEP_SIZE {
-        state.count -= STEP_SIZE;
-
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                let color = if state.is_alive(x, y) {
-                    Color::RED
+    for action in pressed {
+        match action {
+            Action::Restart => {
+                let seed = if app.keyboard.shift() {
+                    state.world.seed()
                 } else {
-                    Color::WHITE
+                    Random::default().gen()
                 };
-
-                state.set_color(color, x, y);
+                reset(state, seed);
             }
+            Action::ToggleDeaths => {
+                state.show_deaths = !state.show_deaths;
+                state.clear_death_fades();
+            }
+            Action::ToggleStats => state.show_stats = !state.show_stats,
+            Action::ToggleHeatmap => state.show_heatmap = !state.show_heatmap,
+            Action::ToggleEvents => state.show_events = !state.show_events,
+            Action::ToggleParticles => state.show_particles = !state.show_particles,
+            Action::ToggleMinimap => state.show_minimap = !state.show_minimap,
+            Action::Screenshot => state.screenshot(),
+            Action::Pause => state.set_paused(!state.paused),
+            Action::StepForward => state.step_forward(),
+            Action::StepBack => state.step_back(),
+            Action::SpeedUp => state.set_speed(state.speed * SPEED_STEP),
+            Action::SlowDown => state.set_speed(state.speed / SPEED_STEP),
+            // Handled by the camera and the tools
+            Action::PanLeft
+            | Action::PanRight
+            | Action::PanUp
+            | Action::PanDown
+            | Action::ZoomIn
+            | Action::ZoomOut
+            | Action::FitView
+            | Action::DragView
+            | Action::UseTool
+            | Action::EraseTool => {}
+        }
+    }
+}
+
+fn update(app: &mut App, state: &mut State) {
+    // The next key or click on the world is bound to the action waiting
+    // for one, and does nothing else
+    let rebinding = state.rebinding.is_some();
+    if let Some(action) = state.rebinding {
+        let input = Input::pressed(app)
+            .filter(|input| matches!(input, Input::Key(_)) || !state.pointer_over_ui);
+        if app.keyboard.was_pressed(KeyCode::Escape) {
+            state.rebinding = None;
+        } else if let Some(input) = input {
+            state.config.keys.set(action, input);
+            state.rebinding = None;
+        }
+    } else {
+        hotkeys(app, state);
+    }
+
+    if !state.paused {
+        state.count += app.timer.delta_f32();
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    state.poll_upload();
+
+    // Sparks keep flying while paused
+    state.particles.update(app.timer.delta_f32());
+
+    if let Some(toast) = &mut state.toast {
+        toast.seconds -= app.timer.delta_f32();
+        if toast.seconds <= 0.0 {
+            state.toast = None;
+        }
+    }
+
+    if let Some(turbo) = state.turbo {
+        state.run_turbo(turbo);
+    }
+    state.run_gif_export();
+
+    let step_size = state.step_size();
+    let max_ticks = (MAX_TICKS_PER_FRAME as f32 * state.speed.max(1.0)).ceil() as u32;
+    let mut ticks = 0;
+    while state.count >= step_size {
+        if ticks == max_ticks {
+            state.count %= step_size;
+            break;
+        }
+
+        state.count -= step_size;
+        ticks += 1;
+
+        state.advance();
+        // A match ending or a replay running out pauses on that very tick
+        if state.paused {
+            state.count = 0.0;
+            break;
+        }
+    }
+
+    if !rebinding {
+        // Clicks on the minimap don't reach the world under it
+        if minimap::control(app, state) {
+            state.pointer_over_ui = true;
         }
+        let keys = &state.config.keys;
+        state
+            .camera
+            .control(app, &state.world, keys, state.pointer_over_ui);
 
-        state.swap_data();
+        // Touches also arrive as left clicks, which mustn't paint
+        #[cfg(target_arch = "wasm32")]
+        let touching = touch::control(app, state);
+        #[cfg(not(target_arch = "wasm32"))]
+        let touching = false;
+        if !touching {
+            tools::apply(app, state);
+        }
     }
+    #[cfg(feature = "gamepad")]
+    gamepad::control(app, state);
+    let clamp = state.config.camera_clamp;
+    state
+        .camera
+        .update(app.timer.delta_f32(), &state.world, clamp);
 
-    check_for_exit(app);
+    // Escape only cancels a rebinding
+    if !rebinding {
+        check_for_exit(app);
+    }
+}
+
+fn event(event: Event) {
+    if let Event::Exit = event {
+        autosave::end_session();
+    }
 }
 
-fn draw(gfx: &mut Graphics, state: &mut State) {
+fn draw(gfx: &mut Graphics, plugins: &mut Plugins, state: &mut State) {
+    // A world of a different size needs a new texture
+    let (width, height) = (state.world.width() as f32, state.world.height() as f32);
+    if state.texture.size() != (width, height) {
+        state.texture = create_texture(gfx, &state.world, &state.bytes);
+        state.dirty = false;
+    }
+
     // Update the texture with the new data
     if state.dirty {
         gfx.update_texture(&mut state.texture)
-            .with_data(&state.current_bytes)
+            .with_data(&state.bytes)
             .update()
             .unwrap();
 
         state.dirty = false;
     }
 
+    let blend = state.blend();
+    if blend.is_some() {
+        if state.previous_texture.size() != (width, height) {
+            state.previous_texture = create_texture(gfx, &state.world, &state.previous_bytes);
+            state.previous_dirty = false;
+        }
+
+        if state.previous_dirty {
+            gfx.update_texture(&mut state.previous_texture)
+                .with_data(&state.previous_bytes)
+                .update()
+                .unwrap();
+
+            state.previous_dirty = false;
+        }
+    }
+
     // Draw the texture using the draw 2d API for convenience
     let mut draw = gfx.create_draw();
     draw.clear(Color::BLACK);
-    draw.image(&state.texture).scale(4.0, 4.0);
-    gfx.render(&draw);
-}
 
-fn index(x: isize, y: isize) -> Option<usize> {
-    if x < 0 || y < 0 {
-        return None;
+    // Everything is drawn in window pixels from the world's top left
+    // corner, wherever the camera put it
+    let size = window_size(gfx.size());
+    let scale = state.camera.scale(size, &state.world);
+    let (x, y) = state.camera.origin(size, &state.world);
+    draw.transform().push(Mat3::from_translation(vec2(x, y)));
+    match blend {
+        Some(blend) => {
+            draw.image(&state.previous_texture).scale(scale, scale);
+            draw.image(&state.texture).scale(scale, scale).alpha(blend);
+        }
+        None => {
+            draw.image(&state.texture).scale(scale, scale);
+        }
     }
 
-    let x = x as usize;
-    let y = y as usize;
-    let index = ((y * WIDTH) + x) * 4;
-    if index >= BYTES_LENGTH {
-        None
-    } else {
-        Some(index)
+    if state.show_heatmap {
+        let (block, interval) = (state.config.heatmap_block, state.config.heatmap_interval);
+        state.heatmap.update(&state.world, block, interval);
+        for ((x, y), size, color) in state.heatmap.blocks() {
+            let size = size as f32 * scale;
+            draw.rect((x as f32 * scale, y as f32 * scale), (size, size))
+                .color(color);
+        }
     }
-}
 
-#[inline]
-fn is_red_color(bytes: &[u8; 4]) -> bool {
-    bytes == &[255, 0, 0, 255]
-}
+    if state.show_particles {
+        for ((x, y), size, color) in state.particles.iter() {
+            let size = size * scale;
+            draw.rect(
+                (x * scale - size / 2.0, y * scale - size / 2.0),
+                (size, size),
+            )
+            .color(color);
+        }
+    }
 
-#[rustfmt::skip]
-fn get_neighbors(ix: isize, iy: isize) -> [(isize, isize); 8] {
-    [
-        (ix - 1, iy - 1), (ix, iy - 1), (ix + 1, iy - 1),
-        (ix - 1, iy),                   (ix + 1, iy),
-        (ix - 1, iy + 1), (ix, iy + 1), (ix + 1, iy + 1),
-    ]
-}
+    if let Some(radius) = state.world.safe_zone_radius() {
+        draw.circle(radius * scale)
+            .position(width * scale / 2.0, height * scale / 2.0)
+            .stroke(2.0)
+            .color(SAFE_ZONE_COLOR);
+    }
+
+    if let Some((x, y)) = state.selected {
+        draw.rect((x as f32 * scale, y as f32 * scale), (scale, scale))
+            .stroke(2.0)
+            .color(Color::WHITE);
+    }
+
+    if let Some((start, end)) = state.erase_box {
+        if let Obstacle::Rect {
+            x,
+            y,
+            width,
+            height,
+        } = tools::box_between(start, end)
+        {
+            draw.rect(
+                (x as f32 * scale, y as f32 * scale),
+                (width as f32 * scale, height as f32 * scale),
+            )
+            .stroke(2.0)
+            .color(Color::RED);
+        }
+    }
+
+    // Frame the world in the winning species' color once the fight is over
+    if let Some(winner) = state.outcome.and_then(|outcome| outcome.winner()) {
+        let color = species_color(&state.world.species()[winner as usize]);
+        draw.rect((0.0, 0.0), (width * scale, height * scale))
+            .stroke(8.0)
+            .color(color);
+    }
+
+    draw.transform().pop();
+    if minimap::is_shown(state) {
+        minimap::draw(&mut draw, size, state);
+    }
 
+    gfx.render(&draw);
+
+    let output = plugins.egui(|ctx| {
+        state.pointer_over_ui = ctx.is_pointer_over_area() || ctx.is_using_pointer();
+
+        #[cfg(target_arch = "wasm32")]
+        touch::style(ctx, state);
+
+        ui::timeline(ctx, state);
+        ui::tools(ctx, state);
+        ui::interactions(ctx, state);
+        ui::inspector(ctx, state);
+        ui::stats(ctx, state);
+        ui::events(ctx, state);
+        ui::legend(ctx, state);
+        ui::clip(ctx, state);
+        ui::keys(ctx, state);
+        ui::match_end(ctx, state);
+        ui::recovery(ctx, state);
+        ui::toast(ctx, state);
+    });
+    gfx.render(&output);
+}
@@ -0,0 +1,147 @@
+//! Per-tick metrics of a run written as CSV for analysis elsewhere.
+
+use alf_core::{SimIoError, SpeciesId, World};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+pub const METRICS_PATH: &str = "metrics.csv";
+
+/// Most CSV the web build buffers before it stops recording, as
+/// localStorage only holds a few megabytes.
+#[cfg(target_arch = "wasm32")]
+pub const MAX_BUFFERED_BYTES: usize = 2 << 20;
+
+/// Where the GUI records metrics: streamed to `METRICS_PATH` on desktop,
+/// buffered until recording stops on the web.
+#[cfg(not(target_arch = "wasm32"))]
+pub type Recording = io::BufWriter<std::fs::File>;
+#[cfg(target_arch = "wasm32")]
+pub type Recording = Vec<u8>;
+
+/// CSV with a row of aggregates per tick, written to `out` as the run goes.
+/// Kill and death counts are running totals.
+pub struct Metrics<W: Write> {
+    out: W,
+    rows: usize,
+}
+
+impl Metrics<Recording> {
+    /// Starts recording the GUI's metrics from `world` as it is now.
+    pub fn start(world: &World) -> Result<Self, SimIoError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let out = io::BufWriter::new(std::fs::File::create(METRICS_PATH)?);
+        #[cfg(target_arch = "wasm32")]
+        let out = Vec::new();
+        Ok(Self::new(world, out)?)
+    }
+
+    /// Finishes writing the GUI's metrics to `METRICS_PATH`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(self) -> Result<(), SimIoError> {
+        self.finish()?;
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(self) -> Result<(), SimIoError> {
+        let csv = self.finish()?;
+        crate::files::write(METRICS_PATH.as_ref(), &csv)
+    }
+
+    /// Whether the buffered CSV has reached `MAX_BUFFERED_BYTES`.
+    #[cfg(target_arch = "wasm32")]
+    pub fn is_full(&self) -> bool {
+        self.out.len() >= MAX_BUFFERED_BYTES
+    }
+}
+
+impl<W: Write> Metrics<W> {
+    /// Writes the CSV header and a row for `world` as it is now to `out`.
+    pub fn new(world: &World, out: W) -> io::Result<Self> {
+        let mut csv = "tick,population,births".to_string();
+        for species in world.species() {
+            let name = &species.name;
+            for column in [
+                "population",
+                "kills",
+                "neighbour deaths",
+                "combat deaths",
+                "environment deaths",
+                "old age deaths",
+                "mean age",
+                "mean health",
+            ] {
+                csv.push(',');
+                csv.push_str(&escape(&format!("{name} {column}")));
+            }
+        }
+        csv.push('\n');
+
+        let mut metrics = Self { out, rows: 0 };
+        metrics.out.write_all(csv.as_bytes())?;
+        metrics.record(world)?;
+        Ok(metrics)
+    }
+
+    pub fn record(&mut self, world: &World) -> io::Result<()> {
+        let species_count = world.species().len();
+        let mut populations = vec![0u64; species_count];
+        let mut ages = vec![0u64; species_count];
+        let mut health = vec![0u64; species_count];
+        let mut births = 0;
+        for y in 0..world.height() {
+            for x in 0..world.width() {
+                if let Some(cell) = world.cell(x, y) {
+                    let species = cell.species as usize;
+                    let age = cell.age(world.tick());
+                    populations[species] += 1;
+                    ages[species] += age as u64;
+                    health[species] += cell.health as u64;
+                    births += (age == 0) as u64;
+                }
+            }
+        }
+
+        let population: u64 = populations.iter().sum();
+        let mut row = format!("{},{population},{births}", world.tick());
+        for id in 0..species_count {
+            let deaths = world.deaths(id as SpeciesId);
+            let mean = |total: u64| total as f64 / populations[id].max(1) as f64;
+            let _ = write!(
+                row,
+                ",{},{},{},{},{},{},{:.2},{:.2}",
+                populations[id],
+                world.kills(id as SpeciesId),
+                deaths.neighbours,
+                deaths.combat,
+                deaths.environment,
+                deaths.old_age,
+                mean(ages[id]),
+                mean(health[id])
+            );
+        }
+        row.push('\n');
+        self.out.write_all(row.as_bytes())?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Flushes the CSV and hands back what it was written to.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+// Species names are user defined, so they may need quoting
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,82 @@
+//! Overview of the whole world in a corner of the window while zoomed in,
+//! with the part in view outlined.
+
+use crate::camera::window_size;
+use crate::input::Action;
+use crate::State;
+use notan::draw::*;
+use notan::prelude::*;
+
+// Length of the minimap's longer side and its gap to the window's edges
+const SIZE: f32 = 200.0;
+const MARGIN: f32 = 10.0;
+
+const BACKGROUND: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+
+/// Whether the minimap is up: it's turned on and the camera is zoomed in
+/// far enough that the world doesn't fit in view.
+pub fn is_shown(state: &State) -> bool {
+    state.show_minimap && state.camera.is_zoomed_in()
+}
+
+/// Recenters the camera on the cell clicked on the minimap, following the
+/// mouse while the tool button is held. Returns whether the pointer is
+/// over the minimap, where clicks are for it alone.
+pub fn control(app: &mut App, state: &mut State) -> bool {
+    if !is_shown(state) {
+        return false;
+    }
+
+    let size = window_size(app.window().size());
+    let (x, y, width, height) = rect(size, state);
+    let (mouse_x, mouse_y) = app.mouse.position();
+    let over = (x..x + width).contains(&mouse_x) && (y..y + height).contains(&mouse_y);
+    if over && state.config.keys.is_down(app, Action::UseTool) {
+        let scale = width / state.world.width() as f32;
+        state
+            .camera
+            .center_on(((mouse_x - x) / scale, (mouse_y - y) / scale));
+    }
+
+    over
+}
+
+pub fn draw(draw: &mut Draw, size: (f32, f32), state: &State) {
+    let (x, y, width, height) = rect(size, state);
+    draw.rect((x - 2.0, y - 2.0), (width + 4.0, height + 4.0))
+        .color(BACKGROUND);
+    draw.image(&state.texture)
+        .position(x, y)
+        .size(width, height);
+
+    // The part of the world in view, cut off at the world's edges
+    let world = &state.world;
+    let scale = width / world.width() as f32;
+    let (left, top) = state.camera.cell_at((0.0, 0.0), size, world);
+    let (right, bottom) = state.camera.cell_at(size, size, world);
+    let (left, top) = (left.max(0.0), top.max(0.0));
+    let right = right.min(world.width() as f32);
+    let bottom = bottom.min(world.height() as f32);
+    if right > left && bottom > top {
+        draw.rect(
+            (x + left * scale, y + top * scale),
+            ((right - left) * scale, (bottom - top) * scale),
+        )
+        .stroke(1.5)
+        .color(Color::WHITE);
+    }
+}
+
+// Position and size of the minimap in the window's bottom right corner,
+// in the world's proportions
+fn rect(size: (f32, f32), state: &State) -> (f32, f32, f32, f32) {
+    let (world_width, world_height) = (state.world.width() as f32, state.world.height() as f32);
+    let scale = SIZE / world_width.max(world_height);
+    let (width, height) = (world_width * scale, world_height * scale);
+    (
+        size.0 - width - MARGIN,
+        size.1 - height - MARGIN,
+        width,
+        height,
+    )
+}
@@ -0,0 +1,103 @@
+//! Short-lived sparks drawn over the world where cells fight, die and are
+//! born.
+
+use crate::coloring::species_color;
+use alf_core::World;
+use notan::prelude::{Color, Random, Rng};
+use std::collections::VecDeque;
+
+// Sparks flying off a cell that died and off one that took damage
+const DEATH_SPARKS: usize = 4;
+const HIT_SPARKS: usize = 1;
+
+const HIT_COLOR: Color = Color::new(1.0, 0.9, 0.3, 1.0);
+
+struct Particle {
+    // Position and velocity in cells and cells per second
+    position: (f32, f32),
+    velocity: (f32, f32),
+    color: Color,
+    // Seconds left to live, out of `lifetime`
+    remaining: f32,
+    lifetime: f32,
+}
+
+#[derive(Default)]
+pub struct Particles {
+    particles: VecDeque<Particle>,
+    rng: Random,
+}
+
+impl Particles {
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// Spawns sparks for what happened in the world's last tick. Past
+    /// `budget` live particles the oldest are dropped for the new ones.
+    pub fn emit(&mut self, world: &World, budget: usize) {
+        for y in 0..world.height() {
+            for x in 0..world.width() {
+                match (world.previous_cell(x, y), world.cell(x, y)) {
+                    // Killed outright or taken over by another species
+                    (Some(before), after)
+                        if after.is_none_or(|after| after.species != before.species) =>
+                    {
+                        let color = species_color(&world.species()[before.species as usize]);
+                        for _ in 0..DEATH_SPARKS {
+                            self.spawn(x, y, color, 3.0, 0.6);
+                        }
+                    }
+                    (Some(before), Some(after))
+                        if before.species == after.species && after.health < before.health =>
+                    {
+                        for _ in 0..HIT_SPARKS {
+                            self.spawn(x, y, HIT_COLOR, 5.0, 0.3);
+                        }
+                    }
+                    (None, Some(after)) => {
+                        let color = species_color(&world.species()[after.species as usize]);
+                        self.spawn(x, y, color.with_alpha(0.5), 1.0, 0.4);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let excess = self.particles.len().saturating_sub(budget);
+        self.particles.drain(..excess);
+    }
+
+    // A particle leaving the middle of the cell in a random direction
+    fn spawn(&mut self, x: usize, y: usize, color: Color, speed: f32, lifetime: f32) {
+        let angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = speed * self.rng.gen_range(0.5..1.0);
+        self.particles.push_back(Particle {
+            position: (x as f32 + 0.5, y as f32 + 0.5),
+            velocity: (angle.cos() * speed, angle.sin() * speed),
+            color,
+            remaining: lifetime,
+            lifetime,
+        });
+    }
+
+    /// Moves the particles on by `dt` seconds, removing spent ones.
+    pub fn update(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.position.0 += particle.velocity.0 * dt;
+            particle.position.1 += particle.velocity.1 * dt;
+            particle.remaining -= dt;
+        }
+        self.particles.retain(|particle| particle.remaining > 0.0);
+    }
+
+    /// Each particle's position and size in cells with its color, fading
+    /// and shrinking as it burns out.
+    pub fn iter(&self) -> impl Iterator<Item = ((f32, f32), f32, Color)> + '_ {
+        self.particles.iter().map(|particle| {
+            let left = particle.remaining / particle.lifetime;
+            let color = particle.color.with_alpha(particle.color.a * left);
+            (particle.position, 0.5 * left, color)
+        })
+    }
+}
@@ -0,0 +1,33 @@
+//! Scenarios shipped with the game, and loading one by name or path.
+
+use crate::Config;
+
+/// Built-in scenarios as names and their config files.
+pub const BUILT_IN: &[(&str, &str)] = &[
+    ("Duel", include_str!("../scenarios/duel.toml")),
+    (
+        "Rock paper scissors",
+        include_str!("../scenarios/rock_paper_scissors.toml"),
+    ),
+    (
+        "Battle royale",
+        include_str!("../scenarios/battle_royale.toml"),
+    ),
+];
+
+pub fn built_in(name: &str) -> Option<Result<Config, String>> {
+    BUILT_IN
+        .iter()
+        .find(|(built_in, _)| built_in.eq_ignore_ascii_case(name))
+        .map(|(name, text)| Config::parse(text, name))
+}
+
+/// Reads the built-in scenario called `name`, or else the scenario file at
+/// that path.
+pub fn load(name: &str) -> Result<Config, String> {
+    built_in(name).unwrap_or_else(|| {
+        let bytes = crate::files::read(name.as_ref())
+            .map_err(|e| format!("Cannot read scenario {name}: {e}"))?;
+        Config::parse(&String::from_utf8_lossy(&bytes), name)
+    })
+}
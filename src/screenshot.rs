@@ -0,0 +1,218 @@
+//! PNG and GIF captures of the world at a resolution independent of the
+//! window.
+
+use crate::coloring::{ColorBy, Palette};
+use crate::config::Arena;
+use alf_core::{History, World};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, ImageOutputFormat, RgbaImage};
+use notan::utils::{Duration, Instant};
+use std::cell::RefCell;
+use std::io::{Cursor, Write};
+use std::rc::Rc;
+
+/// Encodes the part of the RGBA world image `pixels`, `width` cells wide,
+/// covered by `area` (or all of it) as a PNG with `scale` pixels per cell.
+pub fn encode(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    area: Option<Arena>,
+    scale: u32,
+) -> Result<Vec<u8>, String> {
+    let image = render(pixels, width, height, area, scale)?;
+    let mut png = Cursor::new(vec![]);
+    image
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png.into_inner())
+}
+
+/// How a stretch of the history is turned into a GIF.
+pub struct Clip {
+    pub start: u64,
+    pub end: u64,
+    /// Ticks between frames.
+    pub every: u64,
+    /// Milliseconds each frame is shown for.
+    pub frame_ms: u32,
+    pub color_by: ColorBy,
+    pub area: Option<Arena>,
+    pub scale: u32,
+}
+
+/// A GIF of a clip being encoded a few frames at a time, so long clips
+/// don't freeze the window. It works from its own copy of the snapshots
+/// the clip covers, which the live history can't change under it.
+pub struct GifExport {
+    clip: Clip,
+    history: History,
+    // The world at the last frame encoded, or at the start before the first
+    world: World,
+    // Tick of the next frame to encode
+    next: Option<u64>,
+    // Writes into `gif`, finishing the file once dropped
+    encoder: Option<GifEncoder<Buffer>>,
+    gif: Buffer,
+}
+
+// A byte buffer that stays readable while the encoder writes into it
+#[derive(Clone, Default)]
+struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(bytes)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl GifExport {
+    /// Sets up an endlessly looping GIF of `history` from `clip.start` to
+    /// `clip.end`.
+    pub fn new(history: &History, clip: Clip) -> Result<Self, String> {
+        let missing = || format!("tick {} is no longer in the history", clip.start);
+        let history = history.slice(clip.start, clip.end).ok_or_else(missing)?;
+        let world = history.world_at_tick(clip.start).ok_or_else(missing)?;
+
+        let gif = Buffer::default();
+        let mut encoder = GifEncoder::new(gif.clone());
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            next: Some(clip.start),
+            clip,
+            history,
+            world,
+            encoder: Some(encoder),
+            gif,
+        })
+    }
+
+    /// Share of the frames encoded so far, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        let done = self.next.unwrap_or(self.clip.end) - self.clip.start;
+        done as f32 / (self.clip.end - self.clip.start).max(1) as f32
+    }
+
+    /// Encodes frames for up to `budget`, returning the GIF once the last
+    /// one is in.
+    pub fn encode(&mut self, budget: Duration) -> Result<Option<Vec<u8>>, String> {
+        let started = Instant::now();
+        while let Some(tick) = self.next {
+            if started.elapsed() >= budget {
+                return Ok(None);
+            }
+
+            self.seek(tick)?;
+            let pixels = pixels(&self.world, self.clip.color_by);
+            let image = render(
+                &pixels,
+                self.world.width(),
+                self.world.height(),
+                self.clip.area,
+                self.clip.scale,
+            )?;
+            let delay = Delay::from_numer_denom_ms(self.clip.frame_ms, 1);
+            if let Some(encoder) = &mut self.encoder {
+                encoder
+                    .encode_frame(Frame::from_parts(image, 0, 0, delay))
+                    .map_err(|e| e.to_string())?;
+            }
+
+            self.next =
+                (tick < self.clip.end).then(|| (tick + self.clip.every.max(1)).min(self.clip.end));
+        }
+
+        // Dropping the encoder writes the end of the file
+        self.encoder = None;
+        Ok(Some(self.gif.0.take()))
+    }
+
+    // Moves the world on to `tick`. Snapshots may hold edits that stepping
+    // can't reproduce, so the world is rebuilt from the history whenever
+    // one is passed
+    fn seek(&mut self, tick: u64) -> Result<(), String> {
+        let history = &self.history;
+        if history.index_at_or_before(tick) != history.index_at_or_before(self.world.tick()) {
+            self.world = history
+                .world_at_tick(tick)
+                .ok_or_else(|| format!("tick {tick} is no longer in the history"))?;
+        }
+        while self.world.tick() < tick {
+            self.world.step();
+        }
+        Ok(())
+    }
+}
+
+// RGBA image of the world with empty cells in white
+fn pixels(world: &World, color_by: ColorBy) -> Vec<u8> {
+    let palette = Palette::new(world, color_by);
+    (0..world.height())
+        .flat_map(|y| (0..world.width()).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            palette
+                .color(world, x, y)
+                .map_or([255; 4], |color| color.rgba_u8())
+        })
+        .collect()
+}
+
+fn render(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    area: Option<Arena>,
+    scale: u32,
+) -> Result<RgbaImage, String> {
+    let area = area.unwrap_or(Arena {
+        x: 0,
+        y: 0,
+        width,
+        height,
+    });
+    // An area reaching past the world is cut off at its edge
+    let columns = area.width.min(width.saturating_sub(area.x));
+    let rows = area.height.min(height.saturating_sub(area.y));
+    if columns == 0 || rows == 0 {
+        return Err("the screenshot area is outside the world".to_string());
+    }
+
+    let scale = scale.max(1);
+    Ok(RgbaImage::from_fn(
+        columns as u32 * scale,
+        rows as u32 * scale,
+        |x, y| {
+            let cell_x = area.x + (x / scale) as usize;
+            let cell_y = area.y + (y / scale) as usize;
+            let idx = (cell_y * width + cell_x) * 4;
+            image::Rgba([
+                pixels[idx],
+                pixels[idx + 1],
+                pixels[idx + 2],
+                pixels[idx + 3],
+            ])
+        },
+    ))
+}
+
+/// File name of `stem` stamped with the current time in seconds since the
+/// Unix epoch, with `extension`.
+pub fn file_name(stem: &str, extension: &str) -> String {
+    #[cfg(not(target_arch = "wasm32"))]
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+
+    // There's no system clock on the web
+    #[cfg(target_arch = "wasm32")]
+    let seconds = (js_sys::Date::now() / 1000.0) as u64;
+
+    format!("{stem}_{seconds}.{extension}")
+}
@@ -0,0 +1,77 @@
+//! Per-tick statistics of the live world, kept for charting.
+
+use alf_core::World;
+use std::collections::VecDeque;
+
+// Ticks of statistics kept; older samples are dropped
+const MAX_SAMPLES: usize = 2000;
+
+/// Statistics of the world at one tick.
+pub struct Sample {
+    pub tick: u64,
+    /// Live cells per species, indexed by `SpeciesId`.
+    pub populations: Vec<usize>,
+    /// Cells born this tick.
+    pub births: usize,
+    /// Cells that have died since the world was created.
+    pub total_deaths: u64,
+    /// Average age in ticks of the live cells.
+    pub mean_age: f32,
+}
+
+#[derive(Default)]
+pub struct Stats {
+    samples: VecDeque<Sample>,
+}
+
+impl Stats {
+    pub fn samples(&self) -> &VecDeque<Sample> {
+        &self.samples
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Samples the world. After a rewind or branch switch, samples from
+    /// the future it left behind are dropped first.
+    pub fn record(&mut self, world: &World) {
+        while self
+            .samples
+            .back()
+            .is_some_and(|sample| sample.tick >= world.tick())
+        {
+            self.samples.pop_back();
+        }
+
+        let mut populations = vec![0; world.species().len()];
+        let mut births = 0;
+        let mut total_age = 0u64;
+        for y in 0..world.height() {
+            for x in 0..world.width() {
+                if let Some(cell) = world.cell(x, y) {
+                    populations[cell.species as usize] += 1;
+                    let age = cell.age(world.tick());
+                    births += (age == 0) as usize;
+                    total_age += age as u64;
+                }
+            }
+        }
+
+        let population: usize = populations.iter().sum();
+        let total_deaths = (0..world.species().len())
+            .map(|id| world.deaths(id as _).total())
+            .sum();
+
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            tick: world.tick(),
+            populations,
+            births,
+            total_deaths,
+            mean_age: total_age as f32 / population.max(1) as f32,
+        });
+    }
+}
@@ -0,0 +1,230 @@
+//! Mouse tools for editing the live world. The left and right buttons
+//! below are the default Use tool and Erase with tool bindings.
+
+use crate::camera::window_size;
+use crate::input::Action;
+use crate::State;
+use alf_core::{Edit, Obstacle};
+use notan::prelude::*;
+
+/// What clicking on the world does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tool {
+    /// Nothing; clicks only go to the UI.
+    None,
+    /// The left button selects a cell to show in the inspector.
+    Inspect,
+    /// The left button paints cells of a species, the right button kills
+    /// them.
+    Spawn,
+    /// Either button kills cells.
+    Erase,
+    /// Dragging either button kills every cell in the box dragged out.
+    EraseBox,
+    /// The left button paints hazards, the right button clears them.
+    Hazard,
+    /// The left button puts up walls, the right button tears them down.
+    Wall,
+    /// Each left click spawns a number of cells spread over the brush.
+    Scatter,
+}
+
+/// How the scatter tool spreads its cells over the brush.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Distribution {
+    /// Evenly over the whole disc.
+    Disc,
+    /// Along the brush's edge.
+    Ring,
+    /// Bunched up towards the middle.
+    Cluster,
+    /// On a regular grid spaced to fit the count.
+    Grid,
+}
+
+/// Applies the selected tool while a mouse button is held over the world,
+/// and records the edit in the history once it's released.
+pub fn apply(app: &mut App, state: &mut State) {
+    // Ahead of the UI check, so a box released over a window still ends
+    if state.tool == Tool::EraseBox {
+        erase_box(app, state);
+        return;
+    }
+    state.erase_box = None;
+
+    if state.tool == Tool::None || state.pointer_over_ui {
+        return;
+    }
+
+    if state.tool == Tool::Inspect {
+        if state.config.keys.was_pressed(app, Action::UseTool) {
+            state.selected = cell_under_mouse(app, state).map(|(x, y)| (x as usize, y as usize));
+        }
+        return;
+    }
+
+    if state.tool == Tool::Scatter {
+        if state.config.keys.was_pressed(app, Action::UseTool) {
+            if let Some((x, y)) = cell_under_mouse(app, state) {
+                for (x, y) in scatter(
+                    x,
+                    y,
+                    state.brush_radius,
+                    state.scatter_count,
+                    state.distribution,
+                ) {
+                    let species = state.spawn_species;
+                    state.edit(Edit::Spawn { x, y, species });
+                }
+                state.refresh_bytes();
+                state.finish_edit();
+            }
+        }
+        return;
+    }
+
+    let keys = &state.config.keys;
+    let (using, erasing) = (
+        keys.is_down(app, Action::UseTool),
+        keys.is_down(app, Action::EraseTool),
+    );
+    if using || erasing {
+        if let Some((x, y)) = cell_under_mouse(app, state) {
+            state.editing = true;
+
+            let brush = Obstacle::Circle {
+                x,
+                y,
+                radius: state.brush_radius,
+            };
+            for (x, y) in brush.cells() {
+                let edit = match state.tool {
+                    Tool::Spawn if using => Edit::Spawn {
+                        x,
+                        y,
+                        species: state.spawn_species,
+                    },
+                    Tool::Spawn | Tool::Erase => Edit::Kill { x, y },
+                    Tool::Hazard => Edit::Hazard {
+                        x,
+                        y,
+                        damage: if using { state.hazard_damage } else { 0 },
+                    },
+                    Tool::Wall => Edit::Wall { x, y, wall: using },
+                    Tool::None | Tool::Inspect | Tool::EraseBox | Tool::Scatter => continue,
+                };
+                state.edit(edit);
+            }
+
+            state.refresh_bytes();
+        }
+    }
+
+    let keys = &state.config.keys;
+    let released =
+        keys.was_released(app, Action::UseTool) || keys.was_released(app, Action::EraseTool);
+    if state.editing && released {
+        state.editing = false;
+        state.finish_edit();
+    }
+}
+
+// Drags out a box from the cell the button went down on, and kills the
+// cells in it once the button is released
+fn erase_box(app: &mut App, state: &mut State) {
+    let keys = &state.config.keys;
+    let pressed = !state.pointer_over_ui
+        && (keys.was_pressed(app, Action::UseTool) || keys.was_pressed(app, Action::EraseTool));
+    let released =
+        keys.was_released(app, Action::UseTool) || keys.was_released(app, Action::EraseTool);
+
+    let cell = cell_under_mouse(app, state);
+    if pressed {
+        state.erase_box = cell.map(|cell| (cell, cell));
+    } else if let (Some((_, end)), Some(cell)) = (&mut state.erase_box, cell) {
+        // Off the world the box keeps the last corner it had
+        *end = cell;
+    }
+
+    if !released {
+        return;
+    }
+    let Some((start, end)) = state.erase_box.take() else {
+        return;
+    };
+
+    for (x, y) in box_between(start, end).cells() {
+        if state.world.is_alive(x as usize, y as usize) {
+            state.edit(Edit::Kill { x, y });
+        }
+    }
+    state.refresh_bytes();
+    state.finish_edit();
+}
+
+/// The rectangle covering both `start` and `end`, whichever corners they
+/// are.
+pub fn box_between(start: (isize, isize), end: (isize, isize)) -> Obstacle {
+    Obstacle::Rect {
+        x: start.0.min(end.0),
+        y: start.1.min(end.1),
+        width: start.0.abs_diff(end.0) + 1,
+        height: start.1.abs_diff(end.1) + 1,
+    }
+}
+
+fn cell_under_mouse(app: &mut App, state: &State) -> Option<(isize, isize)> {
+    let size = window_size(app.window().size());
+    cell_at(state, app.mouse.position(), size)
+}
+
+/// The cell at `point` in a window of `size`, if it's inside the world.
+pub fn cell_at(state: &State, point: (f32, f32), size: (f32, f32)) -> Option<(isize, isize)> {
+    let world = &state.world;
+    let (x, y) = state.camera.cell_at(point, size, world);
+    let (x, y) = (x.floor(), y.floor());
+
+    let inside = x >= 0.0 && y >= 0.0 && x < world.width() as f32 && y < world.height() as f32;
+    inside.then_some((x as isize, y as isize))
+}
+
+// Cells for `count` spawns spread over the brush at `x`, `y`. Random spreads
+// may pick a cell more than once
+fn scatter(
+    x: isize,
+    y: isize,
+    radius: f32,
+    count: usize,
+    distribution: Distribution,
+) -> Vec<(isize, isize)> {
+    let offset = |dx: f32, dy: f32| (x + dx.round() as isize, y + dy.round() as isize);
+
+    if distribution == Distribution::Grid {
+        // Spacing that fits about `count` points in the disc's area
+        let spacing = (radius * (std::f32::consts::PI / count.max(1) as f32).sqrt()).max(1.0);
+        let steps = (radius / spacing) as isize;
+        let mut cells: Vec<_> = (-steps..=steps)
+            .flat_map(|j| (-steps..=steps).map(move |i| (i as f32, j as f32)))
+            .map(|(i, j)| (i * spacing, j * spacing))
+            .filter(|(dx, dy)| dx * dx + dy * dy <= radius * radius)
+            .map(|(dx, dy)| offset(dx, dy))
+            .collect();
+        cells.truncate(count);
+        return cells;
+    }
+
+    let mut rng = Random::default();
+    (0..count)
+        .map(|_| {
+            let distance = match distribution {
+                // Taking the square root spreads points evenly by area
+                Distribution::Disc => radius * rng.gen::<f32>().sqrt(),
+                Distribution::Ring => radius,
+                Distribution::Cluster => radius * rng.gen::<f32>().powi(2),
+                Distribution::Grid => unreachable!(),
+            };
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            offset(distance * angle.cos(), distance * angle.sin())
+        })
+        .collect()
+}
@@ -0,0 +1,124 @@
+//! Touch controls for the web build: one finger pans, two pinch to zoom,
+//! and a tap selects a cell. Touches also reach egui as mouse clicks, so
+//! the panels keep working; they're made roomier once a touch is seen.
+
+use crate::camera::window_size;
+use crate::{tools, State};
+use notan::egui::{self, Context};
+use notan::prelude::*;
+
+// A touch let go within this many seconds that moved less than this many
+// pixels is a tap
+const TAP_TIME: f32 = 0.3;
+const TAP_SLOP: f32 = 10.0;
+
+#[derive(Default)]
+pub struct Touches {
+    /// Set by the first touch; the UI is enlarged from then on.
+    pub detected: bool,
+    styled: bool,
+    // Fingers down last frame, by id, with their positions
+    last: Vec<(u8, (f32, f32))>,
+    // Where the current one-finger touch started and for how long it's
+    // been down, while it could still be a tap
+    tap: Option<((f32, f32), f32)>,
+    // Set when the gesture started over the UI, which handles it instead
+    over_ui: bool,
+}
+
+/// Pans, zooms and selects with the fingers on the world. Returns whether
+/// a touch is in progress or just ended, in which case its emulated mouse
+/// clicks shouldn't use the tools.
+pub fn control(app: &mut App, state: &mut State) -> bool {
+    let size = window_size(app.window().size());
+    let dt = app.timer.delta_f32();
+
+    let touch = &app.touch;
+    let mut fingers: Vec<(u8, (f32, f32))> = touch
+        .down
+        .keys()
+        .filter_map(|id| Some((*id, touch.position(*id)?)))
+        .collect();
+    fingers.sort_by_key(|(id, _)| *id);
+    let released = !touch.released.is_empty();
+
+    let touches = &mut state.touches;
+    if !touch.pressed.is_empty() {
+        touches.detected = true;
+        if fingers.len() == 1 {
+            touches.over_ui = state.pointer_over_ui;
+            touches.tap = Some((fingers[0].1, 0.0));
+        }
+    }
+
+    if let Some((start, held)) = &mut touches.tap {
+        *held += dt;
+        let moved = fingers
+            .first()
+            .is_some_and(|(_, (x, y))| (x - start.0).hypot(y - start.1) > TAP_SLOP);
+        if fingers.len() > 1 || moved {
+            touches.tap = None;
+        }
+    }
+
+    let same_fingers = fingers.len() == touches.last.len()
+        && fingers.iter().zip(&touches.last).all(|(a, b)| a.0 == b.0);
+    if !touches.over_ui && same_fingers {
+        match (fingers.as_slice(), touches.last.as_slice()) {
+            // Dragging pans once it's clearly not a tap
+            ([(_, now)], [(_, before)]) if touches.tap.is_none() => {
+                let delta = (now.0 - before.0, now.1 - before.1);
+                state.camera.pan(delta, size, &state.world);
+            }
+            // Two fingers zoom about their midpoint and pan with it
+            ([(_, a), (_, b)], [(_, last_a), (_, last_b)]) => {
+                let middle = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+                let last_middle = ((last_a.0 + last_b.0) / 2.0, (last_a.1 + last_b.1) / 2.0);
+                let spread = (a.0 - b.0).hypot(a.1 - b.1);
+                let last_spread = (last_a.0 - last_b.0).hypot(last_a.1 - last_b.1);
+
+                let delta = (middle.0 - last_middle.0, middle.1 - last_middle.1);
+                state.camera.pan(delta, size, &state.world);
+                if spread > 0.0 && last_spread > 0.0 {
+                    let factor = spread / last_spread;
+                    state.camera.pinch(factor, middle, size, &state.world);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let touches = &mut state.touches;
+    if released && fingers.is_empty() {
+        if let Some((point, held)) = touches.tap.take() {
+            if held < TAP_TIME && !touches.over_ui {
+                state.selected =
+                    tools::cell_at(state, point, size).map(|(x, y)| (x as usize, y as usize));
+            }
+        }
+    }
+
+    let touches = &mut state.touches;
+    let active = !fingers.is_empty() || released;
+    touches.last = fingers;
+    active
+}
+
+/// Enlarges buttons and spacing for fingers once a touch has been seen.
+pub fn style(ctx: &Context, state: &mut State) {
+    let touches = &mut state.touches;
+    if !touches.detected || touches.styled {
+        return;
+    }
+    touches.styled = true;
+
+    let mut style = (*ctx.style()).clone();
+    style.spacing.interact_size = egui::vec2(48.0, 36.0);
+    style.spacing.button_padding = egui::vec2(12.0, 8.0);
+    style.spacing.item_spacing = egui::vec2(10.0, 10.0);
+    style.spacing.slider_width = 180.0;
+    for font in style.text_styles.values_mut() {
+        font.size *= 1.25;
+    }
+    ctx.set_style(style);
+}
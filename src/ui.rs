@@ -0,0 +1,897 @@
+use crate::coloring::{self, ColorBy};
+use crate::events::EventKind;
+use crate::input::Action;
+use crate::metrics::METRICS_PATH;
+use crate::stats::Sample;
+use crate::tools::{Distribution, Tool};
+use crate::{files, scenarios, State, MAX_SPEED, MIN_SPEED, REPLAY_PATH};
+use alf_core::{Edit, HistoryMode, MatchEnd, ResumeCheck, Species, SpeciesId};
+use notan::egui::plot::{Legend, Line, Plot, PlotPoints};
+use notan::egui::{self, Color32, Context};
+use notan::prelude::{Random, Rng};
+
+const MIB: f32 = 1024.0 * 1024.0;
+
+pub fn timeline(ctx: &Context, state: &mut State) {
+    egui::Window::new("Timeline").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            let label = if state.paused { "Play" } else { "Pause" };
+            if ui.button(label).clicked() {
+                state.set_paused(!state.paused);
+            }
+
+            let keys = &state.config.keys;
+            let hint = |text: &str, action| format!("{text} ({})", keys.get(action));
+            let back = hint("Back one tick", Action::StepBack);
+            let forward = hint("Forward one tick", Action::StepForward);
+            let stats = hint("Charts of the run so far", Action::ToggleStats);
+            let events = hint("Log of kills, extinctions and more", Action::ToggleEvents);
+            let heatmap = hint(
+                "Overlay of where the cells are densest",
+                Action::ToggleHeatmap,
+            );
+            let minimap = hint(
+                "Overview of the world while zoomed in",
+                Action::ToggleMinimap,
+            );
+            let speed_hint = format!(
+                "{:.1} ticks per second ({} / {})",
+                state.config.ticks_per_second * state.speed,
+                keys.get(Action::SlowDown),
+                keys.get(Action::SpeedUp)
+            );
+            let particles = hint(
+                "Sparks where cells fight, die and are born",
+                Action::ToggleParticles,
+            );
+
+            if ui.button("<").on_hover_text(back).clicked() {
+                state.step_back();
+            }
+
+            if ui.button(">").on_hover_text(forward).clicked() {
+                state.step_forward();
+            }
+
+            ui.label(format!("Tick {}", state.world.tick()));
+
+            let mut speed = state.speed;
+            let slider = egui::Slider::new(&mut speed, MIN_SPEED..=MAX_SPEED)
+                .logarithmic(true)
+                .suffix("x");
+            if ui.add(slider).on_hover_text(speed_hint).changed() {
+                state.set_speed(speed);
+            }
+            ui.toggle_value(&mut state.show_stats, "Stats")
+                .on_hover_text(stats);
+            ui.toggle_value(&mut state.show_events, "Events")
+                .on_hover_text(events);
+            ui.toggle_value(&mut state.show_heatmap, "Heatmap")
+                .on_hover_text(heatmap);
+            ui.toggle_value(&mut state.show_minimap, "Minimap")
+                .on_hover_text(minimap);
+            ui.toggle_value(&mut state.smooth, "Smooth")
+                .on_hover_text("Fade between ticks instead of jumping");
+            if ui
+                .toggle_value(&mut state.show_particles, "Particles")
+                .on_hover_text(particles)
+                .changed()
+            {
+                state.particles.clear();
+            }
+
+            let color_by = state.color_by;
+            egui::ComboBox::from_id_source("color_by")
+                .selected_text(format!("Color by {}", color_by.name().to_lowercase()))
+                .show_ui(ui, |ui| {
+                    for option in ColorBy::ALL {
+                        ui.selectable_value(&mut state.color_by, option, option.name());
+                    }
+                });
+            if state.color_by != color_by {
+                state.refresh_bytes();
+            }
+        });
+
+        ui.horizontal(|ui| match state.turbo {
+            Some(turbo) => {
+                let done = state.world.tick() - turbo.from;
+                let total = turbo.to - turbo.from;
+                let progress = egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                    .text(format!("Turbo: {done} / {total} ticks"))
+                    .desired_width(240.0);
+                ui.add(progress);
+                if ui.button("Cancel").clicked() {
+                    state.turbo = None;
+                }
+            }
+            None => {
+                ui.label("Turbo");
+                ui.add(
+                    egui::DragValue::new(&mut state.turbo_ticks)
+                        .clamp_range(1..=10_000_000)
+                        .suffix(" ticks"),
+                );
+                if ui
+                    .button("Run")
+                    .on_hover_text("Step as fast as possible without drawing every tick")
+                    .clicked()
+                {
+                    state.start_turbo(state.turbo_ticks);
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            match &state.recording {
+                Some(replay) => {
+                    if ui
+                        .button(format!("Stop recording ({} ticks)", replay.ticks))
+                        .clicked()
+                    {
+                        state.stop_recording();
+                    }
+                }
+                None => {
+                    if ui.button("Record").clicked() {
+                        state.start_recording();
+                    }
+                }
+            }
+
+            match &state.metrics {
+                Some(metrics) => {
+                    if ui
+                        .button(format!("Stop metrics ({} ticks)", metrics.rows()))
+                        .on_hover_text(format!("Write them to {METRICS_PATH}"))
+                        .clicked()
+                    {
+                        state.stop_metrics();
+                    }
+                }
+                None => {
+                    if ui
+                        .button("Record metrics")
+                        .on_hover_text("Log per-tick stats as CSV")
+                        .clicked()
+                    {
+                        state.start_metrics();
+                    }
+                }
+            }
+
+            if ui.button("Play replay").clicked() {
+                state.play_replay(REPLAY_PATH.as_ref());
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                if let Some(path) = files::pick_save_path() {
+                    state.save(&path);
+                }
+            }
+
+            if ui.button("Load").clicked() {
+                if let Some(path) = files::pick_load_path() {
+                    state.load(&path);
+                }
+            }
+
+            let mut load = None;
+            ui.add_enabled_ui(!state.recent_files.paths().is_empty(), |ui| {
+                ui.menu_button("Recent", |ui| {
+                    for path in state.recent_files.paths() {
+                        if ui.button(path.display().to_string()).clicked() {
+                            load = Some(path.clone());
+                            ui.close_menu();
+                        }
+                    }
+                });
+            });
+
+            if let Some(path) = load {
+                state.load(&path);
+            }
+
+            ui.menu_button("Scenarios", |ui| {
+                for (name, _) in scenarios::BUILT_IN {
+                    let button = ui.button(*name).on_hover_ui(|ui| {
+                        // Only parsed while hovered
+                        if let Some(Ok(config)) = scenarios::built_in(name) {
+                            ui.label(config.description.unwrap_or_default());
+                        }
+                    });
+                    if button.clicked() {
+                        match scenarios::built_in(name) {
+                            Some(Ok(config)) => state.start_scenario(config),
+                            Some(Err(err)) => state.report_error(err),
+                            None => {}
+                        }
+                        ui.close_menu();
+                    }
+                }
+
+                ui.separator();
+                if ui.button("Open...").clicked() {
+                    if let Some(path) = files::pick_scenario_load_path() {
+                        state.load_scenario(&path);
+                    }
+                    ui.close_menu();
+                }
+
+                if ui.button("Save as...").clicked() {
+                    if let Some(path) = files::pick_scenario_save_path() {
+                        state.save_scenario(&path);
+                    }
+                    ui.close_menu();
+                }
+
+                if ui
+                    .button("Clear world")
+                    .on_hover_text("Kill every cell and pause to lay out a scenario")
+                    .clicked()
+                {
+                    state.clear_world();
+                    ui.close_menu();
+                }
+            });
+
+            let key = state.config.keys.get(Action::Screenshot).to_string();
+            if ui.button("Screenshot").on_hover_text(key).clicked() {
+                state.screenshot();
+            }
+
+            if ui.button("Export GIF").clicked() {
+                // Start from the last 100 recorded ticks, a frame each
+                let end = recorded_ticks(state).1;
+                state.clip = Some((end.saturating_sub(100), end, 1));
+            }
+
+            ui.toggle_value(&mut state.show_keys, "Keys");
+
+            if ui.button("Reload config").clicked() {
+                state.reload_config();
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            {
+                if ui.button("Download").clicked() {
+                    state.download();
+                }
+
+                if ui.button("Upload").clicked() {
+                    state.upload();
+                }
+            }
+        });
+
+        let history = state.timeline.history();
+        if !history.is_empty() {
+            let last = history.len() - 1;
+            let mut index = state.rewound.unwrap_or(last);
+            let slider = egui::Slider::new(&mut index, 0..=last).show_value(false);
+            if ui.add(slider).changed() {
+                state.rewind_to(index);
+            }
+        }
+
+        ui.horizontal(|ui| {
+            let mut mode = state.timeline.history().mode();
+            ui.label("Store snapshots as");
+            ui.radio_value(&mut mode, HistoryMode::Delta, "Deltas");
+            ui.radio_value(&mut mode, HistoryMode::Resimulate, "Re-simulation");
+            state.timeline.history_mut().set_mode(mode);
+        });
+
+        let history = state.timeline.history();
+        ui.label(format!(
+            "History: {} snapshots, {:.1} MiB ({:.1} / {:.1} MiB in all branches)",
+            history.len(),
+            history.bytes() as f32 / MIB,
+            state.timeline.bytes() as f32 / MIB,
+            state.timeline.max_bytes() as f32 / MIB
+        ));
+
+        ui.separator();
+        ui.checkbox(
+            &mut state.branch_on_resume,
+            "Branch when resuming from the past",
+        );
+
+        let mut switch_to = None;
+        for (id, branch) in state.timeline.branches().iter().enumerate() {
+            let label = match branch.parent {
+                Some((parent, tick)) => format!(
+                    "{} (from {} at tick {})",
+                    branch.name,
+                    state.timeline.branches()[parent].name,
+                    tick
+                ),
+                None => branch.name.clone(),
+            };
+
+            if ui
+                .selectable_label(id == state.timeline.active(), label)
+                .clicked()
+            {
+                switch_to = Some(id);
+            }
+        }
+
+        if let Some(branch) = switch_to {
+            state.switch_branch(branch);
+        }
+    });
+}
+
+/// Shows the latest toast at the bottom of the window until it times out or
+/// is clicked away.
+pub fn toast(ctx: &Context, state: &mut State) {
+    let Some(toast) = &state.toast else {
+        return;
+    };
+
+    let clicked = egui::Area::new("toast")
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -16.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style())
+                .show(ui, |ui| {
+                    if toast.error {
+                        ui.colored_label(ui.visuals().error_fg_color, &toast.message);
+                    } else {
+                        ui.label(&toast.message);
+                    }
+                })
+                .response
+                .interact(egui::Sense::click())
+                .clicked()
+        })
+        .inner;
+
+    if clicked {
+        state.toast = None;
+    }
+}
+
+/// Offers to restore the latest autosave after the previous session crashed.
+pub fn recovery(ctx: &Context, state: &mut State) {
+    let Some(path) = state.recovery.clone() else {
+        return;
+    };
+
+    egui::Window::new("Restore autosave?")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "The last session didn't exit cleanly. Restore {}?",
+                path.display()
+            ));
+
+            ui.horizontal(|ui| {
+                if ui.button("Restore").clicked() {
+                    state.recovery = None;
+                    state.load(&path);
+                    state.set_paused(false);
+                }
+
+                if ui.button("Discard").clicked() {
+                    state.recovery = None;
+                    state.set_paused(false);
+                }
+            });
+        });
+}
+
+pub fn describe_outcome(outcome: MatchEnd, species: &[Species]) -> String {
+    let name = |id: SpeciesId| &species[id as usize].name;
+    match outcome {
+        MatchEnd::LastStanding(id) => format!("{} eliminated every other species", name(id)),
+        MatchEnd::KillLimit(id) => format!("{} reached the kill limit", name(id)),
+        MatchEnd::TimeLimit(Some(id)) => format!("Time's up, {} has the most cells", name(id)),
+        MatchEnd::TimeLimit(None) => "Time's up with a tie".to_string(),
+        MatchEnd::Extinction => "Every species died out".to_string(),
+    }
+}
+
+pub fn describe_resume(check: ResumeCheck) -> String {
+    match check {
+        ResumeCheck::Unchecked => "The save has no hashes to check resuming against".to_string(),
+        ResumeCheck::Verified => "Verified the save resumes deterministically".to_string(),
+        ResumeCheck::Diverged(tick) => {
+            format!("The save doesn't resume the way it ran, it diverges at tick {tick}")
+        }
+    }
+}
+
+/// Summary shown when a match ends, with buttons to play it again.
+pub fn match_end(ctx: &Context, state: &mut State) {
+    let Some(outcome) = state.outcome else {
+        return;
+    };
+
+    let mut open = state.show_outcome;
+    let mut rematch = None;
+    egui::Window::new("Match over")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            let world = &state.world;
+            ui.heading(describe_outcome(outcome, world.species()));
+            ui.label(format!("Lasted {} ticks", world.tick()));
+
+            egui::Grid::new("match_stats").striped(true).show(ui, |ui| {
+                ui.strong("Species");
+                ui.strong("Survivors");
+                ui.strong("Kills");
+                ui.strong("Killed");
+                ui.strong("Died of age");
+                ui.end_row();
+
+                for (id, (species, population)) in
+                    world.species().iter().zip(world.populations()).enumerate()
+                {
+                    let [r, g, b, a] = species.color;
+                    let color = Color32::from_rgba_unmultiplied(r, g, b, a);
+                    ui.colored_label(color, &species.name);
+                    ui.label(population.to_string());
+                    ui.label(world.kills(id as SpeciesId).to_string());
+                    let deaths = world.deaths(id as SpeciesId);
+                    ui.label(deaths.combat.to_string());
+                    ui.label(deaths.old_age.to_string());
+                    ui.end_row();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("Rematch").clicked() {
+                    rematch = Some(world.seed());
+                }
+
+                if ui.button("New match").clicked() {
+                    rematch = Some(Random::default().gen());
+                }
+            });
+        });
+    state.show_outcome = open;
+
+    if let Some(seed) = rematch {
+        crate::reset(state, seed);
+        state.set_paused(false);
+    }
+}
+
+pub fn tools(ctx: &Context, state: &mut State) {
+    egui::Window::new("Tools").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut state.tool, Tool::None, "None");
+            ui.radio_value(&mut state.tool, Tool::Inspect, "Inspect");
+            ui.radio_value(&mut state.tool, Tool::Spawn, "Spawn");
+            ui.radio_value(&mut state.tool, Tool::Erase, "Erase");
+            ui.radio_value(&mut state.tool, Tool::EraseBox, "Erase box");
+            ui.radio_value(&mut state.tool, Tool::Hazard, "Hazard");
+            ui.radio_value(&mut state.tool, Tool::Wall, "Wall");
+            ui.radio_value(&mut state.tool, Tool::Scatter, "Scatter");
+        });
+
+        if !matches!(state.tool, Tool::None | Tool::Inspect | Tool::EraseBox) {
+            ui.add(egui::Slider::new(&mut state.brush_radius, 0.0..=20.0).text("Brush radius"));
+        }
+
+        if matches!(state.tool, Tool::Spawn | Tool::Scatter) {
+            let species = state.world.species();
+            // The world may have been replaced by one with fewer species
+            if state.spawn_species as usize >= species.len() {
+                state.spawn_species = 0;
+            }
+
+            egui::ComboBox::from_label("Species")
+                .selected_text(&species[state.spawn_species as usize].name)
+                .show_ui(ui, |ui| {
+                    for (id, species) in species.iter().enumerate() {
+                        ui.selectable_value(
+                            &mut state.spawn_species,
+                            id as SpeciesId,
+                            &species.name,
+                        );
+                    }
+                });
+        }
+
+        if state.tool == Tool::Spawn {
+            ui.label("Left click spawns cells, right click kills them");
+        }
+
+        if state.tool == Tool::Scatter {
+            ui.add(egui::Slider::new(&mut state.scatter_count, 1..=500).text("Cells per click"));
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut state.distribution, Distribution::Disc, "Disc");
+                ui.radio_value(&mut state.distribution, Distribution::Ring, "Ring");
+                ui.radio_value(&mut state.distribution, Distribution::Cluster, "Cluster");
+                ui.radio_value(&mut state.distribution, Distribution::Grid, "Grid");
+            });
+        }
+
+        if state.tool == Tool::EraseBox {
+            ui.label("Drag a box to kill every cell in it");
+        }
+
+        if state.tool == Tool::Hazard {
+            ui.add(egui::Slider::new(&mut state.hazard_damage, 1..=10).text("Damage per tick"));
+            ui.label("Left click paints hazards, right click clears them");
+        }
+
+        if state.tool == Tool::Wall {
+            ui.label("Left click puts up walls, right click tears them down");
+        }
+    });
+}
+
+/// Table of which species attack which, editable while the world runs.
+pub fn interactions(ctx: &Context, state: &mut State) {
+    let mut toggled = None;
+    egui::Window::new("Interactions").show(ctx, |ui| {
+        ui.label("Rows attack the checked columns");
+
+        let species = state.world.species();
+        egui::Grid::new("interactions").show(ui, |ui| {
+            ui.label("");
+            for victim in species {
+                ui.label(&victim.name);
+            }
+            ui.end_row();
+
+            for (attacker_id, attacker) in species.iter().enumerate() {
+                let attacker_id = attacker_id as SpeciesId;
+                ui.label(&attacker.name);
+                for victim_id in 0..species.len() as SpeciesId {
+                    let mut preys = attacker.preys_on(attacker_id, victim_id);
+                    let checkbox = ui.add_enabled(
+                        attacker_id != victim_id,
+                        egui::Checkbox::new(&mut preys, ""),
+                    );
+                    if checkbox.changed() {
+                        toggled = Some((attacker_id, victim_id, preys));
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    });
+
+    if let Some((attacker, victim, preys)) = toggled {
+        state.edit(Edit::Prey {
+            attacker,
+            victim,
+            preys,
+        });
+        state.finish_edit();
+    }
+}
+
+/// Live details of the cell selected with the inspect tool.
+pub fn inspector(ctx: &Context, state: &mut State) {
+    let Some((x, y)) = state.selected else {
+        return;
+    };
+
+    // The world may have been replaced by a smaller one
+    if x >= state.world.width() || y >= state.world.height() {
+        state.selected = None;
+        return;
+    }
+
+    let mut close = false;
+    egui::SidePanel::right("inspector").show(ctx, |ui| {
+        let world = &state.world;
+        ui.heading(format!("Cell {x}, {y}"));
+
+        egui::Grid::new("inspector_stats").show(ui, |ui| {
+            match world.cell(x, y) {
+                Some(cell) => {
+                    let species = &world.species()[cell.species as usize];
+                    let age = cell.age(world.tick());
+
+                    ui.label("Species");
+                    ui.label(&species.name);
+                    ui.end_row();
+
+                    ui.label("Health");
+                    ui.label(format!("{} / {}", cell.health, species.health));
+                    ui.end_row();
+
+                    ui.label("Age");
+                    match species.lifespan {
+                        Some(lifespan) => ui.label(format!("{age} / {lifespan} ticks")),
+                        None => ui.label(format!("{age} ticks")),
+                    };
+                    ui.end_row();
+
+                    ui.label("Attack");
+                    ui.label(species.attack_at(age).to_string());
+                    ui.end_row();
+                }
+                None => {
+                    let contents = if world.is_wall(x, y) { "Wall" } else { "Empty" };
+                    ui.label(contents);
+                    ui.end_row();
+                }
+            }
+
+            if world.hazard(x, y) > 0 {
+                ui.label("Hazard");
+                ui.label(format!("{} damage per tick", world.hazard(x, y)));
+                ui.end_row();
+            }
+        });
+
+        close = ui.button("Close").clicked();
+    });
+
+    if close {
+        state.selected = None;
+    }
+}
+
+/// Charts of how the run has gone, one sample per tick.
+pub fn stats(ctx: &Context, state: &mut State) {
+    let samples = state.stats.samples();
+    let species = state.world.species();
+
+    egui::Window::new("Stats")
+        .open(&mut state.show_stats)
+        .show(ctx, |ui| {
+            let line = |value: &dyn Fn(&Sample) -> f64| {
+                let points: PlotPoints = samples
+                    .iter()
+                    .map(|sample| [sample.tick as f64, value(sample)])
+                    .collect();
+                Line::new(points)
+            };
+
+            ui.label("Population");
+            Plot::new("populations")
+                .height(150.0)
+                .include_y(0.0)
+                .legend(Legend::default())
+                .show(ui, |plot| {
+                    for (id, species) in species.iter().enumerate() {
+                        let [r, g, b, a] = species.color;
+                        let population = |sample: &Sample| {
+                            sample.populations.get(id).copied().unwrap_or(0) as f64
+                        };
+                        plot.line(
+                            line(&population)
+                                .color(Color32::from_rgba_unmultiplied(r, g, b, a))
+                                .name(&species.name),
+                        );
+                    }
+                });
+
+            ui.label("Births and deaths per tick");
+            let deaths: Vec<_> = samples
+                .iter()
+                .zip(samples.iter().skip(1))
+                .map(|(before, sample)| {
+                    let deaths = sample.total_deaths.saturating_sub(before.total_deaths);
+                    [sample.tick as f64, deaths as f64]
+                })
+                .collect();
+            Plot::new("births_deaths")
+                .height(150.0)
+                .include_y(0.0)
+                .legend(Legend::default())
+                .show(ui, |plot| {
+                    plot.line(line(&|sample| sample.births as f64).name("Births"));
+                    plot.line(Line::new(PlotPoints::new(deaths)).name("Deaths"));
+                });
+
+            ui.label("Mean age");
+            Plot::new("mean_age")
+                .height(100.0)
+                .include_y(0.0)
+                .show(ui, |plot| {
+                    plot.line(line(&|sample| sample.mean_age as f64));
+                });
+        });
+}
+
+/// Scrollable log of events, filtered by kind. Clicking one jumps to its
+/// tick.
+pub fn events(ctx: &Context, state: &mut State) {
+    let mut jump = None;
+    egui::Window::new("Events")
+        .open(&mut state.show_events)
+        .show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for kind in EventKind::ALL {
+                    let mut shown = state.event_filter.contains(&kind);
+                    if ui.checkbox(&mut shown, kind.name()).changed() {
+                        state.event_filter.retain(|filtered| *filtered != kind);
+                        if shown {
+                            state.event_filter.push(kind);
+                        }
+                    }
+                }
+            });
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    let events = state.events.events().iter();
+                    for event in events.filter(|event| state.event_filter.contains(&event.kind)) {
+                        let label = format!("{:>6}  {}", event.tick, event.text);
+                        if ui
+                            .selectable_label(event.tick == state.world.tick(), label)
+                            .clicked()
+                        {
+                            jump = Some(event.tick);
+                        }
+                    }
+                });
+        });
+
+    if let Some(tick) = jump {
+        state.jump_to(tick);
+    }
+}
+
+/// Color scale of the attribute cells are colored by, when it isn't species.
+pub fn legend(ctx: &Context, state: &State) {
+    let (low, high) = match state.color_by {
+        ColorBy::Species => return,
+        ColorBy::Health => ("Hurt".to_string(), "Full health".to_string()),
+        ColorBy::Age => {
+            let oldest = coloring::oldest(&state.world).unwrap_or(0);
+            ("Newborn".to_string(), format!("{oldest} ticks"))
+        }
+    };
+
+    egui::Area::new("legend")
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(low);
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = 0.0;
+                        for step in 0..=10 {
+                            let [r, g, b, _] = coloring::gradient(step as f32 / 10.0).rgba_u8();
+                            let size = egui::vec2(10.0, 14.0);
+                            let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                            ui.painter()
+                                .rect_filled(rect, 0.0, Color32::from_rgb(r, g, b));
+                        }
+                    });
+                    ui.label(high);
+                });
+            });
+        });
+}
+
+/// Picks the stretch of the history to export as a GIF, then shows how far
+/// along the export is.
+pub fn clip(ctx: &Context, state: &mut State) {
+    if let Some(export) = &state.gif_export {
+        let progress = export.progress();
+        let mut cancel = false;
+        egui::Window::new("Exporting GIF")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.add(egui::ProgressBar::new(progress).show_percentage());
+                cancel = ui.button("Cancel").clicked();
+            });
+        if cancel {
+            state.gif_export = None;
+        }
+        return;
+    }
+
+    let Some((mut start, mut end, mut every)) = state.clip else {
+        return;
+    };
+    let (first, last) = recorded_ticks(state);
+
+    let mut open = true;
+    let mut export = false;
+    egui::Window::new("Export GIF")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("clip").show(ui, |ui| {
+                ui.label("From tick");
+                ui.add(egui::DragValue::new(&mut start).clamp_range(first..=last));
+                ui.end_row();
+
+                ui.label("To tick");
+                ui.add(egui::DragValue::new(&mut end).clamp_range(start..=last));
+                ui.end_row();
+
+                ui.label("Ticks per frame");
+                ui.add(egui::DragValue::new(&mut every).clamp_range(1..=100));
+                ui.end_row();
+            });
+
+            let end = end.max(start);
+            ui.label(format!(
+                "{} frames at {} pixels per cell",
+                (end - start) / every.max(1) + 1,
+                state.config.screenshot_scale
+            ));
+            export = ui.button("Export").clicked();
+        });
+
+    let end = end.max(start);
+    state.clip = (open && !export).then_some((start, end, every));
+    if export {
+        state.export_clip(start, end, every);
+    }
+}
+
+// First and last tick still in the history
+fn recorded_ticks(state: &State) -> (u64, u64) {
+    let history = state.timeline.history();
+    let first = history.first_tick().unwrap_or(0);
+    let last = history.latest().map_or(first, |world| world.tick());
+    (first, last)
+}
+
+/// Lists what each action is bound to, rebinding one to the next key or
+/// click on the world.
+pub fn keys(ctx: &Context, state: &mut State) {
+    let mut open = state.show_keys;
+    egui::Window::new("Keys").open(&mut open).show(ctx, |ui| {
+        egui::Grid::new("keys").striped(true).show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(action.name());
+                let label = match state.rebinding {
+                    Some(rebinding) if rebinding == action => "Press a key...".to_string(),
+                    _ => state.config.keys.get(action).to_string(),
+                };
+                if ui.button(label).clicked() {
+                    state.rebinding = Some(action);
+                }
+
+                let clashes: Vec<_> = state
+                    .config
+                    .keys
+                    .clashes(action)
+                    .map(Action::name)
+                    .collect();
+                if clashes.is_empty() {
+                    ui.label("");
+                } else {
+                    let message = format!("Also {}", clashes.join(", "));
+                    ui.colored_label(ui.visuals().warn_fg_color, message);
+                }
+                ui.end_row();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if state.rebinding.is_some() && ui.button("Cancel").clicked() {
+                state.rebinding = None;
+            }
+            if ui.button("Defaults").clicked() {
+                state.config.keys.reset();
+            }
+            if ui
+                .button("Save")
+                .on_hover_text(format!("Write the keys to {}", state.config_path.display()))
+                .clicked()
+            {
+                state.save_config();
+            }
+        });
+    });
+
+    state.show_keys = open;
+    if !open {
+        state.rebinding = None;
+    }
+}